@@ -0,0 +1,175 @@
+//! Front matter linter.
+
+use std::{
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use color_eyre::eyre::{self, Context, bail};
+use note::parser::{FrontMatterError, check, editor::FrontMatterEditor, schema::FieldValue};
+use tracing::{debug, info};
+
+use crate::{cli::CheckFormat, config::Config, list::strip_note_prefix, query::index::walk_notes};
+
+use super::Diagnostic;
+
+/// Reports the problems in the front matter of every note, fixing the mechanical ones if
+/// requested.
+pub fn check_front_matter(config: &Config, format: CheckFormat, fix: bool) -> eyre::Result<()> {
+    let mut diagnostics = Vec::new();
+
+    for path in walk_notes(&config.note_path)? {
+        let rel_path = strip_note_prefix(&config.note_path, &path)?.to_owned();
+
+        let mut content = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("couldn't read file {}", path.display()))?;
+
+        let errors = match check(&content, &config.front_matter) {
+            Ok(errors) => errors,
+            Err(err) => {
+                let diagnostic = match err.position() {
+                    Some(position) => {
+                        Diagnostic::at_position(rel_path, &content, position, message(&err))
+                    }
+                    None => Diagnostic::new(rel_path, 1, 1, message(&err)),
+                };
+
+                diagnostics.push(diagnostic);
+
+                continue;
+            }
+        };
+
+        let mut changed = false;
+
+        for error in errors {
+            let mut diagnostic = match error.position() {
+                Some(position) => {
+                    Diagnostic::at_position(rel_path.clone(), &content, position, message(&error))
+                }
+                None => Diagnostic::new(rel_path.clone(), 1, 1, message(&error)),
+            };
+
+            if fix && let Some(fixed) = fix_error(&path, &content, &error)? {
+                content = fixed;
+                changed = true;
+
+                diagnostic = diagnostic.fixed();
+            }
+
+            diagnostics.push(diagnostic);
+        }
+
+        if changed {
+            info!(path = %path.display(), "fixed front matter");
+
+            fs::write(&path, &content)
+                .wrap_err_with(|| format!("couldn't write file {}", path.display()))?;
+        }
+    }
+
+    let mut out = io::stdout().lock();
+
+    match format {
+        CheckFormat::Text => {
+            for diagnostic in &diagnostics {
+                writeln!(out, "{diagnostic}")?;
+            }
+        }
+        CheckFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &diagnostics)?;
+            writeln!(out)?;
+        }
+    }
+
+    let problems = diagnostics.iter().filter(|d| !d.is_fixed()).count();
+
+    if problems > 0 {
+        bail!("found {problems} problems in the front matter");
+    }
+
+    Ok(())
+}
+
+/// Formats the error with all its sources.
+fn message(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+
+    while let Some(err) = source {
+        message.push_str(": ");
+        message.push_str(&err.to_string());
+
+        source = err.source();
+    }
+
+    message
+}
+
+/// Returns the content with the error fixed, if it's a mechanical one.
+///
+/// - a missing `created` is set to the modification date of the file,
+/// - a missing `description` is set to an empty string,
+/// - dates in other common formats are converted to `Y-m-d`.
+fn fix_error(path: &Path, content: &str, error: &FrontMatterError) -> eyre::Result<Option<String>> {
+    let (Some(name), Ok(mut editor)) = (error.field(), FrontMatterEditor::new(content)) else {
+        return Ok(None);
+    };
+
+    let value = match error {
+        FrontMatterError::FieldMissing(name) if name == "created" => {
+            let modified = fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .wrap_err_with(|| {
+                    format!("couldn't read modification time of {}", path.display())
+                })?;
+
+            FieldValue::Date(DateTime::<Local>::from(modified).date_naive())
+        }
+        FrontMatterError::FieldMissing(name) if name == "description" => {
+            FieldValue::String(String::new())
+        }
+        FrontMatterError::Date { name, .. } => {
+            let Some(FieldValue::String(value)) = editor.get(name) else {
+                return Ok(None);
+            };
+
+            let Some(date) = normalize_date(&value) else {
+                return Ok(None);
+            };
+
+            FieldValue::Date(date)
+        }
+        _ => return Ok(None),
+    };
+
+    debug!("fixing {name} with {value}");
+
+    editor.set(name, &value);
+
+    Ok(Some(editor.finish()))
+}
+
+/// Parses dates like `2025/01/31` or `2025-01-31T10:00:00Z`.
+fn normalize_date(value: &str) -> Option<NaiveDate> {
+    const DATE_FORMATS: [&str; 3] = ["%Y/%m/%d", "%Y.%m.%d", "%d.%m.%Y"];
+    const DATE_TIME_FORMATS: [&str; 3] =
+        ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"];
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.date_naive());
+    }
+
+    DATE_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(value, fmt).ok())
+        .or_else(|| {
+            DATE_TIME_FORMATS
+                .iter()
+                .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+                .map(|date_time| date_time.date())
+        })
+}
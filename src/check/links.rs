@@ -0,0 +1,204 @@
+//! Broken links checker.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, Context, bail};
+use note::parser::{error::Report, link::Link, slugify};
+use tracing::{debug, warn};
+
+use crate::{
+    config::Config,
+    links::Resolver,
+    list::{parse_note, strip_note_prefix},
+    query::index::walk_notes,
+};
+
+use super::Diagnostic;
+
+/// Anchors of the linked notes, `None` if the note couldn't be parsed.
+type Anchors = HashMap<PathBuf, Option<HashSet<String>>>;
+
+/// Reports the links to missing files, to missing headings and outside the notes directory.
+///
+/// The notes that can't be parsed are reported too, since their links can't be checked.
+pub fn check_links(config: &Config) -> eyre::Result<()> {
+    let notes = walk_notes(&config.note_path)?;
+    let resolver = Resolver::new(&config.note_path, &notes);
+
+    let mut anchors = Anchors::new();
+    let mut broken = 0usize;
+    let mut failed = 0usize;
+
+    for path in &notes {
+        let content = fs::read_to_string(path)
+            .wrap_err_with(|| format!("couldn't read file {}", path.display()))?;
+
+        let rel_path = strip_note_prefix(&config.note_path, path)?;
+
+        let markdown = match parse_note(config, path, &content)? {
+            Ok(markdown) => markdown,
+            Err(err) => {
+                let message = format!(
+                    "couldn't parse the note: {}",
+                    Report::new(&err, None, &content)
+                );
+                let diagnostic = match err.position() {
+                    Some(position) => {
+                        Diagnostic::at_position(rel_path.to_owned(), &content, position, message)
+                    }
+                    None => Diagnostic::new(rel_path.to_owned(), 1, 1, message),
+                };
+
+                println!("{diagnostic}");
+
+                failed += 1;
+
+                continue;
+            }
+        };
+
+        for link in &markdown.links {
+            let Some(message) = check_link(config, &resolver, &mut anchors, path, link) else {
+                continue;
+            };
+
+            let diagnostic =
+                Diagnostic::at_offset(rel_path.to_owned(), &content, link.offset, message);
+
+            println!("{diagnostic}");
+
+            broken += 1;
+        }
+    }
+
+    if failed > 0 {
+        bail!("found {broken} broken links, couldn't parse {failed} notes");
+    }
+
+    if broken > 0 {
+        bail!("found {broken} broken links");
+    }
+
+    Ok(())
+}
+
+/// Returns the description of the problem if the link is broken.
+fn check_link(
+    config: &Config,
+    resolver: &Resolver<'_>,
+    anchors: &mut Anchors,
+    source: &Path,
+    link: &Link,
+) -> Option<String> {
+    let target = resolver.resolve(source, link);
+
+    debug!("link {} resolved to {}", link.target, target.display());
+
+    if !target.starts_with(&config.note_path) {
+        return Some(format!(
+            "link to {} escapes the notes directory",
+            link.target
+        ));
+    }
+
+    if !target.exists() {
+        return Some(format!("link to missing file {}", link.target));
+    }
+
+    let anchor = link.anchor.as_deref()?;
+
+    if target.extension().is_none_or(|ext| ext != "md") {
+        return None;
+    }
+
+    let ids = anchors
+        .entry(target.clone())
+        .or_insert_with(|| read_anchors(config, &target))
+        .as_ref()?;
+
+    if ids.contains(anchor) || ids.contains(&slugify(anchor)) {
+        return None;
+    }
+
+    let target = if link.target.is_empty() {
+        "the note"
+    } else {
+        &link.target
+    };
+
+    Some(format!("missing heading #{anchor} in {target}"))
+}
+
+/// Returns the anchors of the headings in the note, the note itself is reported if it can't be
+/// parsed.
+fn read_anchors(config: &Config, path: &Path) -> Option<HashSet<String>> {
+    let content = fs::read_to_string(path)
+        .inspect_err(|err| warn!("couldn't read file {}: {err}", path.display()))
+        .ok()?;
+
+    let markdown = parse_note(config, path, &content)
+        .inspect_err(|err| warn!("{err:#}"))
+        .ok()?
+        .inspect_err(|err| debug!("couldn't parse {}: {:#}", path.display(), err))
+        .ok()?;
+
+    Some(markdown.heading_ids().into_iter().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use note::parser::link::LinkKind;
+
+    use super::*;
+
+    #[test]
+    fn should_accept_wiki_links_to_note_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            note_path: dir.path().to_owned(),
+            ..Default::default()
+        };
+
+        let source = config.note_path.join("a.md");
+        let target = config.note_path.join("b_note.md");
+
+        fs::write(
+            &target,
+            "---\ntitle: \"B\"\ndescription: \"\"\ncreated: \"2025-01-01\"\n---\n\n# Some heading\n",
+        )
+        .unwrap();
+
+        let plain = config.note_path.join("plain.md");
+
+        fs::write(&plain, "# Plain\n\n## Other heading\n").unwrap();
+
+        let notes = [source.clone(), target, plain];
+        let resolver = Resolver::new(&config.note_path, &notes);
+        let mut anchors = Anchors::new();
+
+        let wiki = |target: &str, anchor: Option<&str>| Link {
+            kind: LinkKind::Wiki,
+            target: target.to_string(),
+            anchor: anchor.map(str::to_string),
+            offset: 0,
+        };
+
+        let mut check = |link| check_link(&config, &resolver, &mut anchors, &source, &link);
+
+        assert_eq!(check(wiki("b note", None)), None);
+        assert_eq!(check(wiki("B Note", Some("some-heading"))), None);
+        assert_eq!(check(wiki("plain", Some("other-heading"))), None);
+        assert_eq!(
+            check(wiki("plain", Some("missing"))),
+            Some("missing heading #missing in plain".to_string())
+        );
+        assert_eq!(
+            check(wiki("c note", None)),
+            Some("link to missing file c note".to_string())
+        );
+    }
+}
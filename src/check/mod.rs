@@ -0,0 +1,93 @@
+//! Checks of the notes in the vault.
+
+use std::{fmt::Display, path::PathBuf};
+
+use note::parser::error::{Position, snippet};
+use serde::Serialize;
+
+pub(crate) mod frontmatter;
+pub(crate) mod links;
+
+/// Problem found in a note.
+///
+/// This is the schema of the objects printed by the `json` format.
+#[derive(Debug, Serialize)]
+pub(crate) struct Diagnostic {
+    /// Path of the note relative to the notes directory.
+    path: PathBuf,
+    /// Line of the problem, starting from 1.
+    line: usize,
+    /// Column of the problem in characters, starting from 1.
+    column: usize,
+    message: String,
+    /// The problem was fixed automatically.
+    fixed: bool,
+    /// Line of the note with the problem.
+    #[serde(skip)]
+    snippet: Option<String>,
+}
+
+impl Diagnostic {
+    fn new(path: PathBuf, line: usize, column: usize, message: String) -> Self {
+        Self {
+            path,
+            line,
+            column,
+            message,
+            fixed: false,
+            snippet: None,
+        }
+    }
+
+    /// Creates a diagnostic at the position in the content of the note.
+    fn at_position(path: PathBuf, content: &str, position: Position, message: String) -> Self {
+        Self {
+            snippet: snippet(content, position),
+            ..Self::new(path, position.line, position.column, message)
+        }
+    }
+
+    fn fixed(self) -> Self {
+        Self {
+            fixed: true,
+            ..self
+        }
+    }
+
+    fn is_fixed(&self) -> bool {
+        self.fixed
+    }
+
+    /// Creates a diagnostic at the byte offset in the content of the note.
+    fn at_offset(path: PathBuf, content: &str, offset: usize, message: String) -> Self {
+        Self::at_position(
+            path,
+            content,
+            Position::from_offset(content, offset),
+            message,
+        )
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )?;
+
+        if self.fixed {
+            write!(f, " (fixed)")?;
+        }
+
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{snippet}")?;
+        }
+
+        Ok(())
+    }
+}
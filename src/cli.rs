@@ -2,9 +2,11 @@
 
 use std::path::PathBuf;
 
+use chrono::NaiveDate;
 use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum, ValueHint};
 use clap_complete::generate;
 use color_eyre::Result;
+use note::parser::Language;
 use regex::Regex;
 
 /// Note taking utility
@@ -21,6 +23,157 @@ pub struct Edit {
     /// Title and name of the file
     #[clap(value_hint(ValueHint::FilePath))]
     pub path: String,
+    /// Template of a new note, by name in the templates directories
+    #[arg(short = 'T', long)]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct Query {
+    /// The search query.
+    pub search: String,
+    /// Format of the results.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: QueryFormat,
+    /// Maximum number of results.
+    #[arg(short = 'n', long, default_value = "10")]
+    pub limit: usize,
+    /// Number of results to skip.
+    #[arg(long, default_value = "0")]
+    pub offset: usize,
+    /// Opens the most relevant note in the editor.
+    #[arg(short, long, conflicts_with = "pick")]
+    pub open: bool,
+    /// Chooses one of the results interactively and opens it in the editor.
+    #[arg(short, long)]
+    pub pick: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct List {
+    /// Path to list.
+    #[arg(value_hint(ValueHint::DirPath))]
+    pub path: Option<PathBuf>,
+
+    #[arg(short = 'd', long, default_value = "1")]
+    pub max_depth: usize,
+
+    /// Fail on notes without a front matter, instead of inferring the title and date.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Sorts the entries, instead of listing the files first and then the directories by name.
+    #[arg(short, long, value_enum)]
+    pub sort: Option<ListSort>,
+
+    /// Reverses the order of the entries.
+    #[arg(short, long)]
+    pub reverse: bool,
+
+    /// Format of the entries.
+    ///
+    /// Except for the text format, the notes that can't be parsed are listed with the error,
+    /// even when filtering.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: ListFormat,
+
+    /// Columns to print for the notes in the text format, separated by commas.
+    #[arg(
+        short,
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [ListColumn::Path, ListColumn::Title]
+    )]
+    pub columns: Vec<ListColumn>,
+
+    #[command(flatten)]
+    pub filter: ListFilter,
+}
+
+/// Output format of the listed entries
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// Tab separated columns, fails on the notes that can't be parsed
+    #[default]
+    Text,
+    /// JSON array of entries
+    Json,
+    /// A JSON entry per line
+    Jsonl,
+    /// Indented tree of the directories and notes
+    Tree,
+}
+
+/// Order of the listed entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListSort {
+    /// Path of the entry
+    Name,
+    /// Title of the note, or path of the directory
+    Title,
+    /// Creation date
+    Created,
+    /// Last update date, or creation date if never updated
+    Updated,
+    /// Size of the file
+    Size,
+}
+
+/// Column of the listed notes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListColumn {
+    Path,
+    Title,
+    Description,
+    /// Comma separated tags
+    Tags,
+    Created,
+    Updated,
+    /// Number of words in the content
+    Words,
+}
+
+/// Filters on the metadata of the listed notes, the directories are not listed if any is set.
+#[derive(Debug, Default, Args)]
+pub struct ListFilter {
+    /// Only notes with the tag, can be repeated to require more tags.
+    #[arg(short, long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    /// Only notes created on or after the date.
+    #[arg(long, value_name = "DATE")]
+    pub created_after: Option<NaiveDate>,
+    /// Only notes created on or before the date.
+    #[arg(long, value_name = "DATE")]
+    pub created_before: Option<NaiveDate>,
+    /// Only notes updated, or created if never updated, on or after the date.
+    #[arg(long, value_name = "DATE")]
+    pub updated_since: Option<NaiveDate>,
+    /// Only notes in the language, `en` also matches `en-GB`.
+    #[arg(short, long)]
+    pub lang: Option<Language>,
+    /// Only released notes.
+    #[arg(long, conflicts_with = "draft")]
+    pub released: bool,
+    /// Only notes not released.
+    #[arg(long)]
+    pub draft: bool,
+}
+
+/// Output format of the query results
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum QueryFormat {
+    /// Human readable list of results
+    #[default]
+    Text,
+    /// JSON array of results
+    Json,
+    /// A JSON result per line
+    Jsonl,
+    /// Tab separated score, path, title, description, tags and snippet
+    Tsv,
+    /// Only the path of the notes
+    Paths,
 }
 
 /// Possible sub commands
@@ -32,14 +185,49 @@ pub enum Command {
     /// Opens the daily journal
     #[command(visible_alias("j"))]
     Journal {
-        /// Journal entry to edit, in the Y-m-d format
-        date: Option<String>,
+        /// Kind of journal, the built-in ones are journal and work
+        #[arg(short, long, default_value = "journal")]
+        kind: String,
+        /// Journal entry to edit, like 2025-01-31, yesterday, -1 or last friday
+        #[arg(allow_negative_numbers = true)]
+        date: Vec<String>,
+        /// Template of a new entry, by name in the templates directories
+        #[arg(short = 'T', long)]
+        template: Option<String>,
     },
     /// Opens the work journal
     #[command(visible_alias("w"))]
     Work {
-        /// Work entry to edit, in the Y-m-d format
-        date: Option<String>,
+        /// Work entry to edit, like 2025-01-31, yesterday, -1 or last friday
+        #[arg(allow_negative_numbers = true)]
+        date: Vec<String>,
+        /// Template of a new entry, by name in the templates directories
+        #[arg(short = 'T', long)]
+        template: Option<String>,
+    },
+    /// Opens the weekly note
+    Week {
+        /// Week to edit, in the ISO Y-Www format like 2025-W07
+        week: Option<String>,
+        /// Template of a new entry, by name in the templates directories
+        #[arg(short = 'T', long)]
+        template: Option<String>,
+    },
+    /// Opens the monthly note
+    Month {
+        /// Month to edit, in the Y-m format
+        month: Option<String>,
+        /// Template of a new entry, by name in the templates directories
+        #[arg(short = 'T', long)]
+        template: Option<String>,
+    },
+    /// Opens the yearly note
+    Year {
+        /// Year to edit
+        year: Option<String>,
+        /// Template of a new entry, by name in the templates directories
+        #[arg(short = 'T', long)]
+        template: Option<String>,
     },
     /// Opens the todo file
     #[command(visible_alias("t"))]
@@ -58,19 +246,31 @@ pub enum Command {
     },
     /// Full text search of all the notes.
     #[clap(visible_alias("q"))]
-    Query {
-        // The search query.
-        search: String,
+    Query(Query),
+    /// List the notes linking to a note.
+    #[command(visible_alias("bl"))]
+    Backlinks {
+        /// Path of the note, relative to the current directory or $NOTE_PATH.
+        #[arg(value_hint(ValueHint::FilePath))]
+        note: PathBuf,
     },
     /// List the notes in $NOTE_PATH or the current directory.
     #[command(visible_alias("ls"))]
-    List {
-        /// Path to list.
-        #[arg(value_hint(ValueHint::DirPath))]
-        path: Option<PathBuf>,
-
-        #[arg(short = 'd', long, default_value = "1")]
-        max_depth: usize,
+    List(List),
+    /// Check the notes for problems.
+    Check {
+        #[command(subcommand)]
+        command: Check,
+    },
+    /// Manage the full text search index.
+    Index {
+        #[command(subcommand)]
+        command: Index,
+    },
+    /// List and edit the tags of the notes.
+    Tags {
+        #[command(subcommand)]
+        command: Tags,
     },
     /// Utility functions like shell completions
     Utils {
@@ -79,6 +279,77 @@ pub enum Command {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum Check {
+    /// Reports links to missing files or headings and links outside $NOTE_PATH
+    Links,
+    /// Reports invalid or missing fields in the front matter of the notes
+    #[command(alias("fm"))]
+    Frontmatter {
+        /// Format of the report.
+        #[arg(short, long, value_enum, default_value_t)]
+        format: CheckFormat,
+        /// Fixes the mechanical issues, like a missing `created` date.
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+/// Output format of the check reports
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CheckFormat {
+    /// A `path:line:column: message` line per problem
+    #[default]
+    Text,
+    /// JSON array of problems
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Index {
+    /// Removes the index and indexes all the notes again
+    Rebuild,
+    /// Shows the number of documents, the last commit and the stale files
+    Status,
+    /// Checks the index files and that every note is indexed and up to date
+    Verify,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Tags {
+    /// Lists the tags with the number of notes using them
+    #[command(visible_alias("ls"))]
+    List,
+    /// Renames a tag in all the notes
+    Rename {
+        from: String,
+        to: String,
+        /// Prints the changes as a diff, without modifying the notes.
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+    /// Replaces the tags with a single one in all the notes
+    Merge {
+        /// Tags to replace.
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Tag to add to the notes.
+        #[arg(short, long)]
+        into: String,
+        /// Prints the changes as a diff, without modifying the notes.
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+    /// Removes a tag from all the notes
+    #[command(visible_alias("rm"))]
+    Delete {
+        tag: String,
+        /// Prints the changes as a diff, without modifying the notes.
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Utils {
     /// Generates shell completions for the given shell
@@ -146,3 +417,49 @@ impl Shell {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_relative_journal_dates() {
+        let parse = |args: &[&str]| {
+            let cli = Cli::try_parse_from(["note"].iter().chain(args)).unwrap();
+
+            match cli.command {
+                Some(Command::Journal {
+                    kind,
+                    date,
+                    template,
+                }) => (kind, date.join(" "), template),
+                command => panic!("unexpected command {command:?}"),
+            }
+        };
+
+        assert_eq!(
+            parse(&["journal", "-1"]),
+            ("journal".to_string(), "-1".to_string(), None)
+        );
+        assert_eq!(
+            parse(&["j", "+2"]),
+            ("journal".to_string(), "+2".to_string(), None)
+        );
+        assert_eq!(
+            parse(&["journal", "last", "friday", "-T", "x"]),
+            (
+                "journal".to_string(),
+                "last friday".to_string(),
+                Some("x".to_string())
+            )
+        );
+        assert_eq!(
+            parse(&["journal", "-k", "work", "-1"]),
+            ("work".to_string(), "-1".to_string(), None)
+        );
+        assert_eq!(
+            parse(&["journal", "yesterday", "--kind", "work"]),
+            ("work".to_string(), "yesterday".to_string(), None)
+        );
+    }
+}
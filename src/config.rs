@@ -1,8 +1,13 @@
 use std::{env, fs, path::PathBuf};
 
+use chrono::{NaiveDate, format::StrftimeItems};
 use color_eyre::eyre::{Context, OptionExt};
+use indexmap::IndexMap;
+use note::parser::{Language, schema::Schema};
 use serde::Deserialize;
 
+use crate::template::TemplatePattern;
+
 #[derive(Debug, Default, Deserialize)]
 struct ConfigFile {
     shell: Option<String>,
@@ -10,6 +15,14 @@ struct ConfigFile {
     note_path: Option<String>,
     find_command: String,
     search_command: String,
+    pick_command: Option<String>,
+    bump_updated: Option<bool>,
+    #[serde(default)]
+    front_matter: Schema,
+    #[serde(default)]
+    journal: IndexMap<String, JournalKind>,
+    #[serde(default)]
+    templates: IndexMap<String, String>,
 }
 
 #[derive(Debug, Default)]
@@ -19,6 +32,74 @@ pub struct Config {
     pub note_path: PathBuf,
     pub find_command: String,
     pub search_command: String,
+    pub pick_command: Option<String>,
+    /// Set the `updated` date of a note after it's modified in the editor, defaults to `true`.
+    pub bump_updated: bool,
+    /// Additional fields of the front matter.
+    pub front_matter: Schema,
+    /// Kinds of journal by name, the built-in `journal` and `work` ones can be redefined.
+    pub journals: IndexMap<String, JournalKind>,
+    /// Directories of the templates, by precedence.
+    pub template_dirs: Vec<PathBuf>,
+    /// Templates of the new notes by path, the first matching one is used.
+    pub templates: Vec<TemplatePattern>,
+}
+
+/// Journal with an entry per day, for example:
+///
+/// ```toml
+/// [journal.standup]
+/// title = "Standup %Y-%m-%d"
+/// description = "Standup of the %d/%m/%Y"
+/// tags = ["journal", "standup"]
+/// language = "en"
+/// template = "standup"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalKind {
+    /// Directory of the entries relative to the notes directory, defaults to the name.
+    pub dir: Option<PathBuf>,
+    pub title: DateFormat,
+    pub description: Option<DateFormat>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub language: Option<Language>,
+    /// Name of the template of the new entries.
+    pub template: Option<String>,
+}
+
+impl JournalKind {
+    fn builtin(title: &str, description: &str, tags: &[&str]) -> Self {
+        Self {
+            dir: None,
+            title: DateFormat(title.to_string()),
+            description: Some(DateFormat(description.to_string())),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            language: "en".parse().ok(),
+            template: None,
+        }
+    }
+}
+
+/// Format of a date with the `strftime` specifiers, checked when reading the configuration.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct DateFormat(String);
+
+impl DateFormat {
+    pub fn format(&self, date: NaiveDate) -> String {
+        date.format(&self.0).to_string()
+    }
+}
+
+impl TryFrom<String> for DateFormat {
+    type Error = chrono::ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        StrftimeItems::new(&value).parse()?;
+
+        Ok(Self(value))
+    }
 }
 
 impl Config {
@@ -27,9 +108,9 @@ impl Config {
             dirs::config_dir().ok_or_eyre("could not find configuration directory")?;
 
         config_dir.push("note");
-        config_dir.push("config.toml");
 
-        let file = fs::read_to_string(config_dir).wrap_err("reading configuration file")?;
+        let file = fs::read_to_string(config_dir.join("config.toml"))
+            .wrap_err("reading configuration file")?;
 
         let config: ConfigFile = toml::from_str(&file).wrap_err("invalid configuration file")?;
 
@@ -54,12 +135,48 @@ impl Config {
             .canonicalize()
             .wrap_err_with(|| format!("couldnt canonicalize path {}", note_path.display()))?;
 
+        let mut journals = IndexMap::from([
+            (
+                "journal".to_string(),
+                JournalKind::builtin(
+                    "Journal %Y-%m-%d",
+                    "Daily notes for the %Y-%m-%d",
+                    &["journal"],
+                ),
+            ),
+            (
+                "work".to_string(),
+                JournalKind::builtin(
+                    "Work %Y-%m-%d",
+                    "Work daily notes for the %Y-%m-%d",
+                    &["journal", "work"],
+                ),
+            ),
+        ]);
+
+        journals.extend(config.journal);
+
+        let templates = config
+            .templates
+            .into_iter()
+            .map(|(glob, name)| {
+                TemplatePattern::new(&glob, name)
+                    .wrap_err_with(|| format!("invalid template pattern {glob}"))
+            })
+            .collect::<color_eyre::Result<_>>()?;
+
         Ok(Self {
             shell,
             editor,
-            note_path,
             find_command: config.find_command,
             search_command: config.search_command,
+            pick_command: config.pick_command,
+            bump_updated: config.bump_updated.unwrap_or(true),
+            front_matter: config.front_matter,
+            journals,
+            template_dirs: vec![note_path.join(".templates"), config_dir.join("templates")],
+            templates,
+            note_path,
         })
     }
 }
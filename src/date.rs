@@ -0,0 +1,119 @@
+//! Dates of the journal entries, written as natural language or relative to today.
+
+use std::str::FromStr;
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+/// Resolves the date relative to `today`.
+///
+/// The accepted formats are:
+///
+/// - `today`, `yesterday` and `tomorrow`;
+/// - a number of days from today, like `-1` or `+2`;
+/// - a weekday, like `monday` or `fri`, for the last one up to today;
+/// - `last` or `next` and a weekday, for the one before or after today;
+/// - an ISO date, like `2025-01-31`;
+/// - an ISO week date, like `2025-W07-3`, or a week, like `2025-W07`, for its Monday.
+pub fn resolve(input: &str, today: NaiveDate) -> Result<NaiveDate, DateError> {
+    let err = || DateError(input.to_string());
+
+    let input = input.trim().to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+
+    let date = match words.as_slice() {
+        ["today"] => Some(today),
+        ["yesterday"] => today.pred_opt(),
+        ["tomorrow"] => today.succ_opt(),
+        ["last", weekday] => {
+            let weekday = Weekday::from_str(weekday).map_err(|_| err())?;
+
+            today.pred_opt().map(|date| last_weekday(date, weekday))
+        }
+        ["next", weekday] => {
+            let weekday = Weekday::from_str(weekday).map_err(|_| err())?;
+            let days = match weekday.days_since(today.weekday()) {
+                0 => 7,
+                days => days,
+            };
+
+            today.checked_add_days(Days::new(days.into()))
+        }
+        [word] if word.starts_with(['+', '-']) => {
+            let days = word.parse::<i64>().map_err(|_| err())?;
+
+            if days < 0 {
+                today.checked_sub_days(Days::new(days.unsigned_abs()))
+            } else {
+                today.checked_add_days(Days::new(days.unsigned_abs()))
+            }
+        }
+        [word] => match Weekday::from_str(word) {
+            Ok(weekday) => Some(last_weekday(today, weekday)),
+            Err(_) => parse_iso(word),
+        },
+        _ => None,
+    };
+
+    date.ok_or_else(err)
+}
+
+/// Returns the last day up to the date that falls on the weekday.
+fn last_weekday(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+    date - Days::new(date.weekday().days_since(weekday).into())
+}
+
+fn parse_iso(input: &str) -> Option<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let input = input.to_uppercase();
+
+    NaiveDate::parse_from_str(&input, "%G-W%V-%u")
+        .or_else(|_| NaiveDate::parse_from_str(&format!("{input}-1"), "%G-W%V-%u"))
+        .ok()
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("couldn't understand the date {0}")]
+pub struct DateError(String);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_resolve_dates() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        // Wednesday
+        let today = date(2025, 2, 12);
+
+        let cases = [
+            ("today", date(2025, 2, 12)),
+            ("Yesterday", date(2025, 2, 11)),
+            ("tomorrow", date(2025, 2, 13)),
+            ("-1", date(2025, 2, 11)),
+            ("+2", date(2025, 2, 14)),
+            ("-30", date(2025, 1, 13)),
+            ("wednesday", date(2025, 2, 12)),
+            ("monday", date(2025, 2, 10)),
+            ("fri", date(2025, 2, 7)),
+            ("last wednesday", date(2025, 2, 5)),
+            ("last friday", date(2025, 2, 7)),
+            ("next wednesday", date(2025, 2, 19)),
+            ("next monday", date(2025, 2, 17)),
+            (" 2025-01-31 ", date(2025, 1, 31)),
+            ("2025-W07", date(2025, 2, 10)),
+            ("2025-w01-3", date(2025, 1, 1)),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(resolve(input, today).unwrap(), expected, "{input}");
+        }
+
+        for invalid in ["", "1", "--1", "last", "last week", "someday", "2025-02-30"] {
+            assert!(resolve(invalid, today).is_err(), "{invalid}");
+        }
+    }
+}
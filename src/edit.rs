@@ -1,52 +1,90 @@
 use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::str::FromStr;
-use std::{fs, io};
+use std::{env, fs, io};
 
 use askama::Template;
-use chrono::{Local, NaiveDate};
+use chrono::{Datelike, Days, Local, NaiveDate};
 use eyre::{self, Context, ensure};
+use note::parser::{Format, editor::FrontMatterEditor, schema::FieldValue};
 
 use sha2::Digest;
 use tracing::{debug, error, info, instrument, trace, warn};
 
-use crate::config::Config;
+use crate::{config::Config, date, list::strip_note_prefix, template};
 
 /// Edit a note
 #[instrument(skip(config))]
-pub fn note(config: &Config, path: &str) -> eyre::Result<()> {
+pub fn note(config: &Config, path: &str, template: Option<&str>) -> eyre::Result<()> {
     let note_path = NoteArgs::parse(&config.note_path, path)?;
 
-    let note = Note::now(note_path.title);
+    let mut note = Note::now(note_path.title);
+
+    note.template = template.map(str::to_string);
 
     note.edit(config, &note_path.path)
 }
 
-/// Edit a journal entry
+/// Edit an existing note, given its path relative to the notes directory
 #[instrument(skip(config))]
-pub fn journal(config: &Config, date: Option<&str>) -> eyre::Result<()> {
-    let entry = JournalArgs::entry("journal", date)?;
+pub fn open(config: &Config, path: &Path, title: String) -> eyre::Result<()> {
+    let note = Note::now(title);
 
-    let mut note = Note::now(format!("Journal {}", entry.date));
+    note.edit(config, path)
+}
 
-    note.description = Some(format!("Daily notes for the {}", entry.date));
-    note.tags.push("journal".to_string());
-    note.lang = Some("en".to_string());
+/// Edit an entry of the journal kind
+#[instrument(skip(config))]
+pub fn journal(
+    config: &Config,
+    kind: &str,
+    date: Option<&str>,
+    template: Option<&str>,
+) -> eyre::Result<()> {
+    let Some(journal) = config.journals.get(kind) else {
+        let kinds: Vec<&str> = config.journals.keys().map(String::as_str).collect();
+
+        eyre::bail!(
+            "unknown journal kind {kind}, expected one of: {}",
+            kinds.join(", ")
+        );
+    };
+
+    let base = journal.dir.as_deref().unwrap_or(Path::new(kind));
+    let entry = JournalArgs::entry(base, date)?;
+
+    let mut note = Note::now(journal.title.format(entry.date));
+
+    note.description = journal
+        .description
+        .as_ref()
+        .map(|description| description.format(entry.date));
+    note.tags.clone_from(&journal.tags);
+    note.lang = journal.language.as_ref().map(ToString::to_string);
+    note.template = template.or(journal.template.as_deref()).map(str::to_string);
+    note.journal_date = Some(entry.date.to_string());
 
     note.edit(config, &entry.path)
 }
 
-/// Edit a work journal entry
+/// Edit a weekly, monthly or yearly note
 #[instrument(skip(config))]
-pub fn work(config: &Config, date: Option<&str>) -> eyre::Result<()> {
-    let entry = JournalArgs::entry("work", date)?;
-
-    let mut note = Note::now(format!("Work {}", entry.date));
-
-    note.description = Some(format!("Work daily notes for the {}", entry.date));
-    note.tags.extend(["journal", "work"].map(str::to_string));
+pub fn periodic(
+    config: &Config,
+    period: Period,
+    date: Option<&str>,
+    template: Option<&str>,
+) -> eyre::Result<()> {
+    let entry = PeriodicEntry::new(period, date)?;
+
+    let mut note = Note::now(format!("{} {}", period.name(), entry.id));
+
+    note.description = Some(format!("{} notes for {}", period.adjective(), entry.id));
+    note.tags
+        .extend(["journal", period.adjective()].map(str::to_lowercase));
     note.lang = Some("en".to_string());
+    note.template = template.map(str::to_string);
+    note.journal_date = Some(entry.id);
 
     note.edit(config, &entry.path)
 }
@@ -77,18 +115,23 @@ impl State {
 }
 
 #[derive(Debug)]
-struct FileHash<F> {
+pub(crate) struct FileHash<F> {
     state: State,
     inner: F,
 }
 
 impl<F> FileHash<F> {
-    fn new(inner: F) -> Self {
+    pub(crate) fn new(inner: F) -> Self {
         Self {
             state: State::new(),
             inner,
         }
     }
+
+    /// Returns the SHA-256 digest of the data read or written so far.
+    pub(crate) fn digest(self) -> Vec<u8> {
+        self.state.hash.finalize().to_vec()
+    }
 }
 
 impl FileHash<io::Sink> {
@@ -153,6 +196,10 @@ struct Note {
     updated_at: Option<String>,
     lang: Option<String>,
     tags: Vec<String>,
+    /// Name of the user template to use instead of this one.
+    template: Option<String>,
+    /// Date or period of a journal entry.
+    journal_date: Option<String>,
 }
 
 impl Note {
@@ -164,11 +211,60 @@ impl Note {
             updated_at: None,
             lang: None,
             tags: Vec::new(),
+            template: None,
+            journal_date: None,
         }
     }
 
-    #[instrument(skip(self))]
-    fn create_note(&self, file: &Path) -> eyre::Result<()> {
+    /// Renders the content of the new note, from the user template if one is chosen or matches
+    /// the path.
+    fn content(&self, config: &Config, note_path: &Path) -> eyre::Result<String> {
+        let rel_path = strip_note_prefix(&config.note_path, note_path)?;
+
+        let name = self.template.as_deref().or_else(|| {
+            config
+                .templates
+                .iter()
+                .find(|pattern| pattern.matches(rel_path))
+                .map(|pattern| pattern.name.as_str())
+        });
+
+        let Some(name) = name else {
+            return Ok(self.render()?);
+        };
+
+        debug!(name, "using user template");
+
+        let source = template::load(&config.template_dirs, name)?;
+
+        let (prev, next) = match self.journal_date {
+            Some(_) => adjacent_entries(&config.note_path.join(rel_path))?,
+            None => (None, None),
+        };
+        let cwd = env::current_dir().wrap_err("couldn't get the current directory")?;
+        let cwd = cwd.to_string_lossy();
+
+        let vars = [
+            ("title", self.title.as_str()),
+            (
+                "description",
+                self.description.as_deref().unwrap_or_default(),
+            ),
+            ("date", self.created_at.as_str()),
+            (
+                "journal_date",
+                self.journal_date.as_deref().unwrap_or_default(),
+            ),
+            ("prev", prev.as_deref().unwrap_or_default()),
+            ("next", next.as_deref().unwrap_or_default()),
+            ("cwd", &cwd),
+        ];
+
+        template::render(&source, &vars).wrap_err_with(|| format!("invalid template {name}"))
+    }
+
+    #[instrument(skip(self, content))]
+    fn create_note(&self, file: &Path, content: &str) -> eyre::Result<()> {
         let file = fs::File::options()
             .write(true)
             .create_new(true)
@@ -176,7 +272,7 @@ impl Note {
 
         let mut file = BufWriter::new(file);
 
-        self.write_into(&mut file)?;
+        file.write_all(content.as_bytes())?;
 
         file.flush()?;
 
@@ -186,11 +282,11 @@ impl Note {
     }
 
     // TODO: modification time
-    #[instrument(skip(self))]
-    fn is_template(&self, file: &Path) -> eyre::Result<bool> {
+    #[instrument(skip(self, content))]
+    fn is_template(&self, file: &Path, content: &str) -> eyre::Result<bool> {
         let mut sink = FileHash::with_sink();
 
-        self.write_into(&mut sink)?;
+        sink.write_all(content.as_bytes())?;
 
         let file = fs::File::open(file)?;
 
@@ -227,19 +323,21 @@ impl Note {
             }
         }
 
-        // Ensure path, if it exists, it has to be a file.
-        let is_new = match fs::metadata(&abs_path) {
+        // Ensure path, if it exists, it has to be a file. The content is the template of a new note.
+        let created = match fs::metadata(&abs_path) {
             Ok(metadata) => {
                 ensure!(metadata.is_file(), "not a file");
 
-                false
+                None
             }
             Err(err) if err.kind() == io::ErrorKind::NotFound => {
                 debug!(file = %abs_path.display(), "file does not exists");
 
-                self.create_note(&abs_path)?;
+                let content = self.content(config, note_path)?;
 
-                true
+                self.create_note(&abs_path, &content)?;
+
+                Some(content)
             }
             Err(err) => {
                 return Err(err)
@@ -247,6 +345,12 @@ impl Note {
             }
         };
 
+        let hash = if created.is_some() {
+            None
+        } else {
+            Some(hash_file(&abs_path)?)
+        };
+
         let status = Command::new(&config.editor)
             .args([&note_path])
             .current_dir(&config.note_path)
@@ -262,16 +366,114 @@ impl Note {
             "editor returned with status code {status}"
         );
 
-        if is_new && self.is_template(&abs_path)? {
+        if let Some(content) = &created
+            && self.is_template(&abs_path, content)?
+        {
             debug!("file was not edited, removing");
 
             fs::remove_file(&abs_path)?;
         }
 
+        if config.bump_updated
+            && let Some(hash) = hash
+            && abs_path.exists()
+            && hash_file(&abs_path)? != hash
+        {
+            bump_updated(&abs_path)?;
+        }
+
         Ok(())
     }
 }
 
+/// Returns the names of the previous and next existing entries in the directory of the entry.
+///
+/// The names of the entries are dates or periods, so they are sorted chronologically.
+fn adjacent_entries(path: &Path) -> eyre::Result<(Option<String>, Option<String>)> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Ok((None, None));
+    };
+
+    let mut prev = None;
+    let mut next = None;
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok((None, None)),
+        Err(err) => {
+            return Err(err).wrap_err_with(|| format!("couldn't read directory {}", dir.display()));
+        }
+    };
+
+    for entry in entries {
+        let entry = entry.wrap_err_with(|| format!("couldn't read directory {}", dir.display()))?;
+        let entry_name = entry.file_name();
+        let entry_path = Path::new(&entry_name);
+
+        if entry_path.extension().is_none_or(|ext| ext != "md") || entry_name == name {
+            continue;
+        }
+
+        if entry_name < name && prev.as_ref().is_none_or(|prev| entry_name > *prev) {
+            prev = Some(entry_name);
+        } else if entry_name > name && next.as_ref().is_none_or(|next| entry_name < *next) {
+            next = Some(entry_name);
+        }
+    }
+
+    let to_string = |name: std::ffi::OsString| name.to_string_lossy().into_owned();
+
+    Ok((prev.map(to_string), next.map(to_string)))
+}
+
+/// Returns the SHA-256 digest of the file content.
+fn hash_file(path: &Path) -> eyre::Result<Vec<u8>> {
+    let file =
+        fs::File::open(path).wrap_err_with(|| format!("couldn't open file {}", path.display()))?;
+
+    let mut file = FileHash::new(BufReader::new(file));
+
+    file.read_to_end(&mut Vec::new())
+        .wrap_err_with(|| format!("couldn't read file {}", path.display()))?;
+
+    Ok(file.digest())
+}
+
+/// Sets the `updated` date of the note to today.
+///
+/// A TOML front matter with a `lastmod` field, like in Hugo, gets the date in it instead.
+#[instrument]
+fn bump_updated(path: &Path) -> eyre::Result<()> {
+    let content = fs::read_to_string(path)
+        .wrap_err_with(|| format!("couldn't read file {}", path.display()))?;
+
+    let Ok(mut editor) = FrontMatterEditor::new(&content) else {
+        warn!("couldn't find the front matter, not updating the date");
+
+        return Ok(());
+    };
+
+    let today = Local::now().date_naive();
+    let key = if editor.format() == Format::Toml && editor.get("lastmod").is_some() {
+        "lastmod"
+    } else {
+        "updated"
+    };
+
+    if editor.get(key) == Some(FieldValue::String(today.to_string())) {
+        return Ok(());
+    }
+
+    editor.set(key, &FieldValue::Date(today));
+
+    fs::write(path, editor.finish())
+        .wrap_err_with(|| format!("couldn't write file {}", path.display()))?;
+
+    info!(%today, "updated date changed");
+
+    Ok(())
+}
+
 #[derive(Debug)]
 struct NoteArgs {
     title: String,
@@ -307,25 +509,27 @@ impl NoteArgs {
     }
 
     fn file_path(base_path: &Path, path: &str) -> PathBuf {
-        let path_str: String = path
-            .chars()
-            .map(|chr| {
-                if chr.is_whitespace() {
-                    '_'
-                } else {
-                    chr.to_ascii_lowercase()
-                }
-            })
-            .collect();
-
         let mut file_path = PathBuf::from(base_path);
-        file_path.push(&path_str);
+        file_path.push(file_name(path));
         file_path.set_extension("md");
 
         file_path
     }
 }
 
+/// Returns the name of the file of a note, lowercase and with the whitespace replaced by `_`.
+pub(crate) fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|chr| {
+            if chr.is_whitespace() {
+                '_'
+            } else {
+                chr.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 struct JournalArgs {
     date: NaiveDate,
@@ -334,10 +538,11 @@ struct JournalArgs {
 
 impl JournalArgs {
     fn entry(base: impl AsRef<Path>, date: Option<&str>) -> eyre::Result<Self> {
+        let today = Local::now().date_naive();
+
         let date = match date {
-            Some(date) => NaiveDate::from_str(date)
-                .wrap_err_with(|| format!("failed to parse date {date}"))?,
-            None => Local::now().date_naive(),
+            Some(date) => date::resolve(date, today)?,
+            None => today,
         };
 
         let mut path = PathBuf::from(base.as_ref());
@@ -347,3 +552,146 @@ impl JournalArgs {
         Ok(Self { date, path })
     }
 }
+
+/// Length of a periodic note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// ISO week, like `2025-W07`.
+    Week,
+    /// Calendar month, like `2025-02`.
+    Month,
+    /// Calendar year, like `2025`.
+    Year,
+}
+
+impl Period {
+    fn name(self) -> &'static str {
+        match self {
+            Period::Week => "Week",
+            Period::Month => "Month",
+            Period::Year => "Year",
+        }
+    }
+
+    fn adjective(self) -> &'static str {
+        match self {
+            Period::Week => "Weekly",
+            Period::Month => "Monthly",
+            Period::Year => "Yearly",
+        }
+    }
+
+    /// Directory of the entries, relative to the notes directory.
+    fn dir(self) -> &'static str {
+        match self {
+            Period::Week => "weekly",
+            Period::Month => "monthly",
+            Period::Year => "yearly",
+        }
+    }
+
+    /// Returns the first day of the period containing the date.
+    fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week => date - Days::new(date.weekday().num_days_from_monday().into()),
+            Period::Month => date.with_day(1).unwrap_or(date),
+            Period::Year => date.with_ordinal(1).unwrap_or(date),
+        }
+    }
+
+    /// Parses the identifier of the period and returns its first day.
+    fn parse(self, id: &str) -> eyre::Result<NaiveDate> {
+        let (date, format) = match self {
+            Period::Week => (format!("{id}-1"), "%G-W%V-%u"),
+            Period::Month => (format!("{id}-01"), "%Y-%m-%d"),
+            Period::Year => (format!("{id}-01-01"), "%Y-%m-%d"),
+        };
+
+        NaiveDate::parse_from_str(&date, format)
+            .wrap_err_with(|| format!("failed to parse {} {id}", self.name().to_lowercase()))
+    }
+
+    /// Returns the identifier of the period starting at the date.
+    fn id(self, start: NaiveDate) -> String {
+        let format = match self {
+            Period::Week => "%G-W%V",
+            Period::Month => "%Y-%m",
+            Period::Year => "%Y",
+        };
+
+        start.format(format).to_string()
+    }
+}
+
+#[derive(Debug)]
+struct PeriodicEntry {
+    id: String,
+    path: PathBuf,
+}
+
+impl PeriodicEntry {
+    fn new(period: Period, id: Option<&str>) -> eyre::Result<Self> {
+        let start = match id {
+            Some(id) => period.parse(id)?,
+            None => period.start(Local::now().date_naive()),
+        };
+
+        let id = period.id(start);
+
+        let mut path = PathBuf::from(period.dir());
+        path.push(&id);
+        path.set_extension("md");
+
+        Ok(Self { id, path })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_periods() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(Period::Week.parse("2025-W07").unwrap(), date(2025, 2, 10));
+        assert_eq!(Period::Week.parse("2025-W01").unwrap(), date(2024, 12, 30));
+        assert_eq!(Period::Month.parse("2025-02").unwrap(), date(2025, 2, 1));
+        assert_eq!(Period::Year.parse("2025").unwrap(), date(2025, 1, 1));
+        assert!(Period::Week.parse("2025-W54").is_err());
+        assert!(Period::Month.parse("2025-13").is_err());
+
+        assert_eq!(Period::Week.start(date(2025, 2, 16)), date(2025, 2, 10));
+        assert_eq!(Period::Week.id(date(2024, 12, 30)), "2025-W01");
+        assert_eq!(Period::Month.id(date(2025, 2, 1)), "2025-02");
+        assert_eq!(Period::Year.id(date(2025, 1, 1)), "2025");
+    }
+
+    #[test]
+    fn should_bump_the_hugo_lastmod() {
+        let dir = tempfile::tempdir().unwrap();
+        let today = Local::now().date_naive();
+
+        let hugo = dir.path().join("hugo.md");
+        let yaml = dir.path().join("yaml.md");
+
+        fs::write(&hugo, "+++\ntitle = \"Post\"\nlastmod = 2024-02-03\n+++\n").unwrap();
+        fs::write(
+            &yaml,
+            "---\ntitle: \"Note\"\nlastmod: \"2024-02-03\"\n---\n",
+        )
+        .unwrap();
+
+        bump_updated(&hugo).unwrap();
+        bump_updated(&yaml).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&hugo).unwrap(),
+            format!("+++\ntitle = \"Post\"\nlastmod = {today}\n+++\n")
+        );
+        assert_eq!(
+            fs::read_to_string(&yaml).unwrap(),
+            format!("---\ntitle: \"Note\"\nlastmod: \"2024-02-03\"\nupdated: \"{today}\"\n---\n")
+        );
+    }
+}
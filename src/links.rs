@@ -0,0 +1,212 @@
+//! Resolution of the links between notes.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, Context, OptionExt};
+use note::parser::link::{Link, LinkKind};
+use tracing::{debug, warn};
+
+use crate::{
+    config::Config,
+    edit::file_name,
+    list::{parse_note, strip_note_prefix},
+    query::index::walk_notes,
+};
+
+/// Resolves the links of the notes to the paths of the linked files.
+#[derive(Debug)]
+pub(crate) struct Resolver<'a> {
+    note_path: &'a Path,
+    /// Notes by file stem normalised like the names of the new notes, for wiki links by name.
+    by_name: HashMap<String, PathBuf>,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn new(note_path: &'a Path, notes: &[PathBuf]) -> Self {
+        let by_name = notes
+            .iter()
+            .filter_map(|path| {
+                let name = file_name(path.file_stem()?.to_str()?);
+
+                Some((name, path.clone()))
+            })
+            .collect();
+
+        Self { note_path, by_name }
+    }
+
+    /// Returns the path of the file the link points to, which may not exist.
+    ///
+    /// Markdown links are relative to the note directory, or to the notes directory if they start
+    /// with a `/`. Wiki links are looked up relative to the note directory, to the notes directory
+    /// and then by file name, also with the name of the file `note edit` creates for the target.
+    pub(crate) fn resolve(&self, source: &Path, link: &Link) -> PathBuf {
+        if link.target.is_empty() {
+            return source.to_owned();
+        }
+
+        let dir = source.parent().unwrap_or(self.note_path);
+
+        match link.kind {
+            LinkKind::Markdown => match link.target.strip_prefix('/') {
+                Some(target) => normalize(&self.note_path.join(target)),
+                None => normalize(&dir.join(&link.target)),
+            },
+            LinkKind::Wiki => {
+                let name = file_name(&link.target);
+                let targets = [with_md(&link.target), with_md(&name)];
+
+                if let Some(found) = targets
+                    .iter()
+                    .flat_map(|target| [dir.join(target), self.note_path.join(target)])
+                    .map(|p| normalize(&p))
+                    .find(|p| p.is_file())
+                {
+                    return found;
+                }
+
+                let [_, target] = targets;
+
+                self.by_name
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or_else(|| normalize(&self.note_path.join(target)))
+            }
+        }
+    }
+}
+
+/// Adds the `.md` extension to a target without one.
+fn with_md(target: &str) -> PathBuf {
+    let mut target = PathBuf::from(target);
+
+    if target.extension().is_none() {
+        target.as_mut_os_string().push(".md");
+    }
+
+    target
+}
+
+/// Lexically removes the `.` and `..` components of the path.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push(component);
+                }
+            }
+            Component::Prefix(_) | Component::RootDir | Component::Normal(_) => out.push(component),
+        }
+    }
+
+    out
+}
+
+/// Lists the notes linking to the given one.
+pub fn backlinks(config: &Config, note: &Path) -> eyre::Result<()> {
+    let target = note_file(config, note)?;
+
+    debug!("backlinks to {}", target.display());
+
+    let notes = walk_notes(&config.note_path)?;
+    let resolver = Resolver::new(&config.note_path, &notes);
+
+    for path in &notes {
+        if *path == target {
+            continue;
+        }
+
+        let content = fs::read_to_string(path)
+            .wrap_err_with(|| format!("couldn't read file {}", path.display()))?;
+
+        let markdown = match parse_note(config, path, &content)? {
+            Ok(markdown) => markdown,
+            Err(err) => {
+                warn!("couldn't parse {}: {:#}", path.display(), err);
+
+                continue;
+            }
+        };
+
+        let links = markdown
+            .links
+            .iter()
+            .any(|link| resolver.resolve(path, link) == target);
+
+        if links {
+            let path = strip_note_prefix(&config.note_path, path)?;
+
+            println!("{}\t{}", path.display(), markdown.title);
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the note file, relative to the current directory or to the notes directory.
+fn note_file(config: &Config, note: &Path) -> eyre::Result<PathBuf> {
+    let mut with_ext = note.to_owned();
+    if with_ext.extension().is_none() {
+        with_ext.set_extension("md");
+    }
+
+    let candidates = [
+        note.to_owned(),
+        with_ext.clone(),
+        config.note_path.join(note),
+        config.note_path.join(&with_ext),
+    ];
+
+    let path = candidates
+        .into_iter()
+        .find(|path| path.is_file())
+        .ok_or_eyre("note not found")?;
+
+    path.canonicalize()
+        .wrap_err_with(|| format!("couldn't get absolute path {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use note::parser::link::LinkKind;
+
+    use super::*;
+
+    #[test]
+    fn should_resolve_wiki_links_to_note_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let note_path = dir.path();
+
+        fs::create_dir(note_path.join("sub")).unwrap();
+
+        let notes = [note_path.join("a.md"), note_path.join("sub/b_note.md")];
+
+        for note in &notes {
+            fs::write(note, "").unwrap();
+        }
+
+        let resolver = Resolver::new(note_path, &notes);
+        let wiki = |target: &str| Link {
+            kind: LinkKind::Wiki,
+            target: target.to_string(),
+            anchor: None,
+            offset: 0,
+        };
+
+        assert_eq!(resolver.resolve(&notes[0], &wiki("sub/B Note")), notes[1]);
+        assert_eq!(resolver.resolve(&notes[0], &wiki("b note")), notes[1]);
+        assert_eq!(resolver.resolve(&notes[1], &wiki("A")), notes[0]);
+        assert_eq!(
+            resolver.resolve(&notes[0], &wiki("missing note")),
+            note_path.join("missing_note.md")
+        );
+    }
+}
@@ -1,14 +1,27 @@
 use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::HashMap,
     fs,
+    io::{self, BufWriter, Write},
     path::{Path, PathBuf},
 };
 
-use color_eyre::eyre::{Context, OptionExt};
-use note::parser::parse;
+use chrono::{DateTime, Local, NaiveDate};
+use color_eyre::eyre::{Context, OptionExt, eyre};
+use note::parser::{
+    FileInfo, Markdown,
+    error::{Error as ParseError, Report},
+    parse_lenient, parse_with,
+};
+use serde::Serialize;
 use tracing::debug;
 use walkdir::{DirEntry, WalkDir};
 
-use crate::config::Config;
+use crate::{
+    cli::{List, ListColumn, ListFilter, ListFormat, ListSort},
+    config::Config,
+};
 
 pub(crate) fn is_hidden(entry: &DirEntry) -> bool {
     entry
@@ -18,19 +31,19 @@ pub(crate) fn is_hidden(entry: &DirEntry) -> bool {
         .unwrap_or(false)
 }
 
-pub fn list_path(
-    config: &Config,
-    path: Option<PathBuf>,
-    max_depth: usize,
-) -> color_eyre::Result<()> {
-    debug!("input path {:?} with max_depth {max_depth}", path);
+pub fn list_path(config: &Config, args: &List) -> color_eyre::Result<()> {
+    debug!(
+        "input path {:?} with max_depth {}",
+        args.path, args.max_depth
+    );
 
-    let path = get_path_to_list(config, path).wrap_err("couldn't get the path to list")?;
+    let path =
+        get_path_to_list(config, args.path.clone()).wrap_err("couldn't get the path to list")?;
 
     debug!("chosen path {}", path.display());
 
     let entries = WalkDir::new(&path)
-        .max_depth(max_depth)
+        .max_depth(args.max_depth)
         .follow_links(true)
         .sort_by(|a, b| {
             // Files first, then compare by name
@@ -50,6 +63,8 @@ pub fn list_path(
         .collect::<Result<Vec<DirEntry>, _>>()
         .wrap_err("couldn't read entry")?;
 
+    let mut listed = Vec::new();
+
     for entry in entries {
         debug!("entry {}", entry.path().display());
 
@@ -59,45 +74,408 @@ pub fn list_path(
             continue;
         }
 
-        print_entry(entry, &config.note_path)?;
+        if let Some(entry) = read_entry(entry, config, args)? {
+            listed.push(entry);
+        }
+    }
+
+    if let Some(sort) = args.sort {
+        listed.sort_by(|a, b| a.cmp_by(b, sort));
+    }
+
+    if args.reverse {
+        listed.reverse();
+    }
+
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    match args.format {
+        ListFormat::Text => {
+            for entry in &listed {
+                entry.write_columns(&mut out, &args.columns)?;
+            }
+        }
+        ListFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &listed)?;
+            writeln!(out)?;
+        }
+        ListFormat::Jsonl => {
+            for entry in &listed {
+                serde_json::to_writer(&mut out, entry)?;
+                writeln!(out)?;
+            }
+        }
+        ListFormat::Tree => {
+            let root = strip_note_prefix(&config.note_path, &path)?;
+
+            write_tree(&mut out, root, &listed)?;
+        }
     }
 
+    out.flush()?;
+
     Ok(())
 }
 
-fn print_entry(entry: DirEntry, note_path: &Path) -> color_eyre::Result<()> {
+/// A listed directory or note.
+///
+/// This is the schema of the objects printed by the `json` and `jsonl` formats, the metadata
+/// fields are present only for the notes that could be parsed.
+#[derive(Debug, Serialize)]
+struct Entry {
+    /// Path relative to the notes directory.
+    path: PathBuf,
+    kind: EntryKind,
+    /// Size of the file in bytes, zero for a directory.
+    size: u64,
+    /// Metadata of the note, [`None`] for a directory or a note that couldn't be parsed.
+    #[serde(flatten)]
+    note: Option<NoteEntry>,
+    /// Reason the note couldn't be parsed, prefixed by the line and column if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum EntryKind {
+    Directory,
+    Note,
+}
+
+#[derive(Debug, Serialize)]
+struct NoteEntry {
+    title: String,
+    /// Description of the note, empty if missing.
+    description: String,
+    /// Tags of the note, sorted alphabetically.
+    tags: Vec<String>,
+    created: NaiveDate,
+    updated: Option<NaiveDate>,
+    words: usize,
+    /// The note has no front matter, the title and the creation date were inferred from the file.
+    missing_metadata: bool,
+}
+
+impl Entry {
+    fn title(&self) -> Cow<'_, str> {
+        match &self.note {
+            Some(note) => Cow::Borrowed(&note.title),
+            None => self.path.to_string_lossy(),
+        }
+    }
+
+    fn cmp_by(&self, other: &Self, sort: ListSort) -> Ordering {
+        let created = |entry: &Self| entry.note.as_ref().map(|note| note.created);
+        let updated = |entry: &Self| {
+            entry
+                .note
+                .as_ref()
+                .map(|note| note.updated.unwrap_or(note.created))
+        };
+
+        let ordering = match sort {
+            ListSort::Name => Ordering::Equal,
+            ListSort::Title => self.title().cmp(&other.title()),
+            ListSort::Created => created(self).cmp(&created(other)),
+            ListSort::Updated => updated(self).cmp(&updated(other)),
+            ListSort::Size => self.size.cmp(&other.size),
+        };
+
+        ordering.then_with(|| self.path.cmp(&other.path))
+    }
+
+    /// Writes the tab separated columns of a note, or the path of a directory followed by a `/`.
+    fn write_columns(&self, out: &mut impl Write, columns: &[ListColumn]) -> io::Result<()> {
+        let Some(note) = &self.note else {
+            return match self.kind {
+                EntryKind::Directory => writeln!(out, "{}/", self.path.display()),
+                // Only listed in the other formats.
+                EntryKind::Note => Ok(()),
+            };
+        };
+
+        for (i, column) in columns.iter().enumerate() {
+            if i > 0 {
+                write!(out, "\t")?;
+            }
+
+            match column {
+                ListColumn::Path => write!(out, "{}", self.path.display())?,
+                ListColumn::Title => write!(out, "{}", note.title)?,
+                ListColumn::Description => write!(out, "{}", note.description)?,
+                ListColumn::Tags => write!(out, "{}", note.tags.join(","))?,
+                ListColumn::Created => write!(out, "{}", note.created)?,
+                ListColumn::Updated => {
+                    if let Some(updated) = note.updated {
+                        write!(out, "{updated}")?;
+                    }
+                }
+                ListColumn::Words => write!(out, "{}", note.words)?,
+            }
+        }
+
+        writeln!(out)
+    }
+}
+
+/// Reads the entry, returns [`None`] if it's not a note or it doesn't match the filters.
+fn read_entry(entry: DirEntry, config: &Config, args: &List) -> color_eyre::Result<Option<Entry>> {
     let metadata = entry
         .metadata()
         .wrap_err_with(|| format!("couldn't read metadata for {}", entry.path().display()))?;
 
-    if metadata.is_dir() {
-        let path = strip_note_prefix(note_path, entry.path())?;
+    let path = strip_note_prefix(&config.note_path, entry.path())?.to_owned();
 
-        println!("{}/", path.display());
+    if metadata.is_dir() {
+        if args.filter.is_set() {
+            return Ok(None);
+        }
 
-        return Ok(());
+        return Ok(Some(Entry {
+            path,
+            kind: EntryKind::Directory,
+            size: 0,
+            note: None,
+            error: None,
+        }));
     }
 
     if entry.path().extension().is_none_or(|ext| ext != "md") {
         debug!("ignoring non markdown file {}", entry.path().display());
 
-        return Ok(());
+        return Ok(None);
     }
 
     let content = fs::read_to_string(entry.path())
         .wrap_err_with(|| format!("couldn't read file {}", entry.path().display()))?;
 
-    let note =
-        parse(&content).wrap_err_with(|| format!("couldn't parse {}", entry.path().display()))?;
+    let note = if args.strict {
+        parse_with(&content, &config.front_matter)
+    } else {
+        let name = entry
+            .path()
+            .file_stem()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let modified = metadata.modified().wrap_err_with(|| {
+            format!(
+                "couldn't read modification time of {}",
+                entry.path().display()
+            )
+        })?;
+
+        parse_lenient(
+            &content,
+            &FileInfo {
+                name: &name,
+                modified: DateTime::<Local>::from(modified).date_naive(),
+            },
+            &config.front_matter,
+        )
+    };
+
+    let note = match note {
+        Ok(note) => note,
+        // The other formats report the error in the entry instead of failing.
+        Err(err) if args.format != ListFormat::Text => {
+            let message = Report::new(&err, None, &content).to_string();
+            let error = match err.position() {
+                Some(position) => format!("{}:{}: {message}", position.line, position.column),
+                None => message,
+            };
+
+            return Ok(Some(Entry {
+                path,
+                kind: EntryKind::Note,
+                size: metadata.len(),
+                note: None,
+                error: Some(error),
+            }));
+        }
+        Err(err) => {
+            return Err(eyre!("{}", err.report(&content)))
+                .wrap_err_with(|| format!("couldn't parse {}", entry.path().display()));
+        }
+    };
+
+    if !args.filter.matches(&note) {
+        return Ok(None);
+    }
+
+    let words = note.word_count();
+    let mut tags = Vec::from_iter(note.tags);
+    tags.sort();
+
+    Ok(Some(Entry {
+        path,
+        kind: EntryKind::Note,
+        size: metadata.len(),
+        error: None,
+        note: Some(NoteEntry {
+            title: note.title,
+            description: note.description,
+            tags,
+            created: note.created,
+            updated: note.updated,
+            words,
+            missing_metadata: note.missing_metadata,
+        }),
+    }))
+}
+
+/// Writes the entries as an indented tree of the directories under the root.
+///
+/// The parents of the entries are shown even when not listed, like with the filters, and the
+/// children keep the order of the listing.
+fn write_tree(out: &mut impl Write, root: &Path, entries: &[Entry]) -> io::Result<()> {
+    let mut children = HashMap::<&Path, Vec<&Path>>::new();
+    let mut listed = HashMap::<&Path, &Entry>::new();
+
+    for entry in entries {
+        let mut path = entry.path.as_path();
+
+        listed.insert(path, entry);
+
+        while let Some(parent) = path.parent().filter(|_| path != root) {
+            let siblings = children.entry(parent).or_default();
+            let known = siblings.contains(&path);
+
+            if !known {
+                siblings.push(path);
+            }
+
+            if known || parent == root {
+                break;
+            }
+
+            path = parent;
+        }
+    }
 
-    let path = strip_note_prefix(note_path, entry.path())?;
+    if root.as_os_str().is_empty() {
+        writeln!(out, "./")?;
+    } else {
+        writeln!(out, "{}/", root.display())?;
+    }
+
+    write_children(out, root, &children, &listed, "")
+}
+
+fn write_children(
+    out: &mut impl Write,
+    parent: &Path,
+    children: &HashMap<&Path, Vec<&Path>>,
+    listed: &HashMap<&Path, &Entry>,
+    indent: &str,
+) -> io::Result<()> {
+    let Some(paths) = children.get(parent) else {
+        return Ok(());
+    };
 
-    println!("{}\t{}", path.display(), note.title);
+    for (i, path) in paths.iter().enumerate() {
+        let last = i + 1 == paths.len();
+        let (branch, nested) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy();
+
+        write!(out, "{indent}{branch}{name}")?;
+
+        match listed.get(path) {
+            Some(Entry {
+                note: Some(note), ..
+            }) if note.missing_metadata => writeln!(out, "  {} (missing metadata)", note.title)?,
+            Some(Entry {
+                note: Some(note), ..
+            }) => writeln!(out, "  {}", note.title)?,
+            Some(Entry {
+                error: Some(error), ..
+            }) => writeln!(out, "  [{error}]")?,
+            _ => writeln!(out, "/")?,
+        }
+
+        write_children(out, path, children, listed, &format!("{indent}{nested}"))?;
+    }
 
     Ok(())
 }
 
-fn strip_note_prefix<'a>(note_path: &'a Path, path: &'a Path) -> color_eyre::Result<&'a Path> {
+impl ListFilter {
+    fn is_set(&self) -> bool {
+        let Self {
+            tags,
+            created_after,
+            created_before,
+            updated_since,
+            lang,
+            released,
+            draft,
+        } = self;
+
+        !tags.is_empty()
+            || created_after.is_some()
+            || created_before.is_some()
+            || updated_since.is_some()
+            || lang.is_some()
+            || *released
+            || *draft
+    }
+
+    fn matches(&self, note: &Markdown) -> bool {
+        let updated = note.updated.unwrap_or(note.created);
+
+        self.tags.iter().all(|tag| note.tags.contains(tag))
+            && self.created_after.is_none_or(|date| note.created >= date)
+            && self.created_before.is_none_or(|date| note.created <= date)
+            && self.updated_since.is_none_or(|date| updated >= date)
+            && self.lang.as_ref().is_none_or(|lang| {
+                note.language.as_ref().is_some_and(|language| {
+                    language.primary() == lang.primary()
+                        && lang
+                            .region()
+                            .is_none_or(|region| language.region() == Some(region))
+                })
+            })
+            && (!self.released || note.released)
+            && (!self.draft || !note.released)
+    }
+}
+
+/// Parses the note even without a front matter, inferring the missing metadata from the file
+/// like `note list` does.
+///
+/// Fails only if the modification time of the file can't be read.
+pub(crate) fn parse_note<'a>(
+    config: &Config,
+    path: &Path,
+    content: &'a str,
+) -> color_eyre::Result<Result<Markdown<'a>, ParseError>> {
+    let name = path
+        .file_stem()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .wrap_err_with(|| format!("couldn't read modification time of {}", path.display()))?;
+
+    let file = FileInfo {
+        name: &name,
+        modified: DateTime::<Local>::from(modified).date_naive(),
+    };
+
+    Ok(parse_lenient(content, &file, &config.front_matter))
+}
+
+pub(crate) fn strip_note_prefix<'a>(
+    note_path: &'a Path,
+    path: &'a Path,
+) -> color_eyre::Result<&'a Path> {
     if path.starts_with(note_path) {
         return path
             .strip_prefix(note_path)
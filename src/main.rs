@@ -7,21 +7,35 @@ use tracing::{debug, trace};
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{
-    cli::{Cli, Command},
-    edit::{journal, note},
+    check::{frontmatter::check_front_matter, links::check_links},
+    cli::{Check, Cli, Command, Index, Tags},
+    edit::{Period, journal, note, periodic},
+    links::backlinks,
     list::list_path,
-    query::query,
+    query::{
+        index::{rebuild, status, verify},
+        query,
+    },
     search::{find_file, grep_content},
+    tags::{delete_tag, list_tags, merge_tags},
 };
 
-use self::edit::work;
-
+mod check;
 mod cli;
 mod config;
+mod date;
 mod edit;
+mod links;
 mod list;
 mod query;
 mod search;
+mod tags;
+mod template;
+
+/// Joins the words of a positional argument, [`None`] if there are none.
+fn join_words(words: &[String]) -> Option<String> {
+    (!words.is_empty()).then(|| words.join(" "))
+}
 
 fn main() -> eyre::Result<()> {
     let cli = Cli::parse();
@@ -51,10 +65,36 @@ fn main() -> eyre::Result<()> {
 
     match cli.command {
         Some(command) => match command {
-            Command::Edit(edit) => note(&config, &edit.path),
-            Command::Journal { date } => journal(&config, date.as_deref()),
-            Command::Work { date } => work(&config, date.as_deref()),
-            Command::Todo => note(&config, "todo"),
+            Command::Edit(edit) => note(&config, &edit.path, edit.template.as_deref()),
+            Command::Journal {
+                kind,
+                date,
+                template,
+            } => journal(
+                &config,
+                &kind,
+                join_words(&date).as_deref(),
+                template.as_deref(),
+            ),
+            Command::Work { date, template } => journal(
+                &config,
+                "work",
+                join_words(&date).as_deref(),
+                template.as_deref(),
+            ),
+            Command::Week { week, template } => {
+                periodic(&config, Period::Week, week.as_deref(), template.as_deref())
+            }
+            Command::Month { month, template } => periodic(
+                &config,
+                Period::Month,
+                month.as_deref(),
+                template.as_deref(),
+            ),
+            Command::Year { year, template } => {
+                periodic(&config, Period::Year, year.as_deref(), template.as_deref())
+            }
+            Command::Todo => note(&config, "todo", None),
             Command::Search { content } => {
                 let content = content.as_deref().unwrap_or("");
 
@@ -68,12 +108,32 @@ fn main() -> eyre::Result<()> {
 
                 Ok(())
             }
-            Command::List { path, max_depth } => list_path(&config, path, max_depth),
-            Command::Query { search } => query(&search, &config),
+            Command::Backlinks { note } => backlinks(&config, &note),
+            Command::List(args) => list_path(&config, &args),
+            Command::Query(args) => query(&args, &config),
+            Command::Check { command } => match command {
+                Check::Links => check_links(&config),
+                Check::Frontmatter { format, fix } => check_front_matter(&config, format, fix),
+            },
+            Command::Index { command } => match command {
+                Index::Rebuild => rebuild(&config),
+                Index::Status => status(&config),
+                Index::Verify => verify(&config),
+            },
+            Command::Tags { command } => match command {
+                Tags::List => list_tags(&config),
+                Tags::Rename { from, to, dry_run } => merge_tags(&config, &[from], &to, dry_run),
+                Tags::Merge {
+                    tags,
+                    into,
+                    dry_run,
+                } => merge_tags(&config, &tags, &into, dry_run),
+                Tags::Delete { tag, dry_run } => delete_tag(&config, &tag, dry_run),
+            },
             Command::Utils { .. } => {
                 unreachable!("already matched");
             }
         },
-        None => note(&config, "inbox"),
+        None => note(&config, "inbox", None),
     }
 }
@@ -0,0 +1,223 @@
+//! Front matter with the positions of its fields.
+//!
+//! The TOML front matter is converted to YAML values, so the fields are read the same way.
+
+use std::collections::HashMap;
+
+use toml::de::{DeTable, DeValue};
+use yaml_rust2::{
+    Yaml, YamlLoader,
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+    yaml::Hash,
+};
+
+use super::{Format, FrontMatterError, error::Position};
+
+/// Top level map of the front matter.
+#[derive(Debug)]
+pub(crate) struct Document {
+    map: Hash,
+    /// Positions of the values of the top level keys.
+    positions: HashMap<String, Position>,
+    /// Position of the start of the front matter.
+    start: Position,
+}
+
+impl Document {
+    /// Loads the front matter `source`, found at `offset` in the `note`.
+    pub(crate) fn load(
+        note: &str,
+        offset: usize,
+        source: &str,
+        format: Format,
+    ) -> Result<Self, FrontMatterError> {
+        match format {
+            Format::Yaml => Self::load_yaml(note, offset, source),
+            Format::Toml => Self::load_toml(note, offset, source),
+        }
+    }
+
+    fn load_yaml(note: &str, offset: usize, source: &str) -> Result<Self, FrontMatterError> {
+        let position = |marker: &Marker| {
+            let byte = source
+                .char_indices()
+                .nth(marker.index())
+                .map_or(source.len(), |(i, _)| i);
+
+            Position::from_offset(note, offset + byte)
+        };
+
+        let mut scan =
+            YamlLoader::load_from_str(source).map_err(|error| FrontMatterError::Yaml {
+                position: position(error.marker()),
+                error,
+            })?;
+
+        if scan.is_empty() {
+            return Err(FrontMatterError::Empty);
+        }
+
+        if scan.len() > 1 && scan[1] != Yaml::Null {
+            return Err(FrontMatterError::MultipleDoc);
+        }
+
+        let doc = scan.swap_remove(0);
+
+        let map = doc.into_hash().ok_or(FrontMatterError::Map)?;
+
+        // The document was already scanned successfully, so errors are not possible here.
+        let mut keys = KeyMarkers::default();
+        let _ = Parser::new_from_str(source).load(&mut keys, false);
+
+        let positions = keys
+            .markers
+            .iter()
+            .map(|(key, marker)| (key.clone(), position(marker)))
+            .collect();
+
+        Ok(Self {
+            map,
+            positions,
+            start: Position::from_offset(note, offset),
+        })
+    }
+
+    fn load_toml(note: &str, offset: usize, source: &str) -> Result<Self, FrontMatterError> {
+        let table = DeTable::parse(source).map_err(|error| FrontMatterError::Toml {
+            message: error.message().to_string(),
+            position: Position::from_offset(
+                note,
+                offset + error.span().map_or(0, |span| span.start),
+            ),
+        })?;
+
+        let mut map = Hash::new();
+        let mut positions = HashMap::new();
+
+        for (key, value) in table.into_inner() {
+            let key = key.into_inner().into_owned();
+
+            positions.insert(
+                key.clone(),
+                Position::from_offset(note, offset + value.span().start),
+            );
+            map.insert(Yaml::String(key), toml_to_yaml(value.into_inner()));
+        }
+
+        Ok(Self {
+            map,
+            positions,
+            start: Position::from_offset(note, offset),
+        })
+    }
+
+    /// Returns the position of the value of the key, or of the start of the front matter.
+    pub(crate) fn position(&self, key: &str) -> Position {
+        self.positions.get(key).copied().unwrap_or(self.start)
+    }
+
+    /// Removes the key, returning its value and position.
+    pub(crate) fn remove(&mut self, key: &str) -> Option<(Yaml, Position)> {
+        let value = self.map.remove(&Yaml::from_str(key))?;
+
+        Some((value, self.position(key)))
+    }
+
+    /// Moves the value of the key `from` to the key `to`, unless `to` is already set.
+    pub(crate) fn rename(&mut self, from: &str, to: &str) {
+        if self.map.contains_key(&Yaml::from_str(to)) {
+            return;
+        }
+
+        let Some(value) = self.map.remove(&Yaml::from_str(from)) else {
+            return;
+        };
+
+        self.map.insert(Yaml::from_str(to), value);
+
+        if let Some(position) = self.positions.remove(from) {
+            self.positions.insert(to.to_string(), position);
+        }
+    }
+
+    /// Returns the remaining fields with a string key, in the order of the document.
+    pub(crate) fn into_entries(self) -> impl Iterator<Item = (String, Yaml)> {
+        self.map
+            .into_iter()
+            .filter_map(|(key, value)| key.into_string().map(|key| (key, value)))
+    }
+}
+
+/// Converts a TOML value, dates are converted to strings like in the YAML front matter.
+pub(crate) fn toml_to_yaml(value: DeValue) -> Yaml {
+    match value {
+        DeValue::String(value) => Yaml::String(value.into_owned()),
+        DeValue::Integer(value) => i64::from_str_radix(value.as_str(), value.radix())
+            .map_or_else(|_| Yaml::String(value.to_string()), Yaml::Integer),
+        DeValue::Float(value) => Yaml::Real(value.as_str().to_string()),
+        DeValue::Boolean(value) => Yaml::Boolean(value),
+        DeValue::Datetime(value) => Yaml::String(value.to_string()),
+        DeValue::Array(items) => Yaml::Array(
+            items
+                .into_iter()
+                .map(|item| toml_to_yaml(item.into_inner()))
+                .collect(),
+        ),
+        DeValue::Table(table) => Yaml::Hash(
+            table
+                .into_iter()
+                .map(|(key, value)| {
+                    (
+                        Yaml::String(key.into_inner().into_owned()),
+                        toml_to_yaml(value.into_inner()),
+                    )
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Records the markers of the values of the top level keys.
+#[derive(Debug, Default)]
+struct KeyMarkers {
+    depth: usize,
+    /// Number of nodes in the top level map, keys and values alternate.
+    nodes: usize,
+    key: Option<String>,
+    markers: Vec<(String, Marker)>,
+}
+
+impl KeyMarkers {
+    fn node(&mut self, scalar: Option<String>, marker: Marker) {
+        if self.nodes.is_multiple_of(2) {
+            self.key = scalar;
+        } else if let Some(key) = self.key.take() {
+            self.markers.push((key, marker));
+        }
+
+        self.nodes += 1;
+    }
+}
+
+impl MarkedEventReceiver for KeyMarkers {
+    fn on_event(&mut self, ev: Event, marker: Marker) {
+        let top = self.depth == 1;
+
+        match ev {
+            Event::MappingStart(..) | Event::SequenceStart(..) => {
+                if top {
+                    self.node(None, marker);
+                }
+
+                self.depth += 1;
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.depth = self.depth.saturating_sub(1);
+            }
+            Event::Scalar(value, ..) if top => self.node(Some(value), marker),
+            Event::Alias(_) if top => self.node(None, marker),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,427 @@
+//! Editing of the front matter of an existing note.
+//!
+//! Only the edited keys are rewritten: the other lines of the front matter, including comments,
+//! and the body of the note are kept byte-for-byte.
+
+use std::ops::Range;
+
+use toml::de::DeTable;
+use winnow::stream::Offset;
+use yaml_rust2::{Yaml, YamlLoader};
+
+use super::{Format, document::toml_to_yaml, error::Error, read_block, schema::FieldValue};
+
+/// Editor of the top level keys of the front matter.
+#[derive(Debug)]
+pub struct FrontMatterEditor<'a> {
+    /// Content before the front matter, the opening delimiter.
+    head: &'a str,
+    /// Lines of the front matter, with their line ending.
+    lines: Vec<String>,
+    /// Content after the front matter, the closing delimiter and the body.
+    tail: &'a str,
+    /// Line ending used by the note.
+    newline: &'static str,
+    format: Format,
+}
+
+impl<'a> FrontMatterEditor<'a> {
+    /// Reads the front matter block at the start of the note.
+    pub fn new(source: &'a str) -> Result<Self, Error> {
+        let mut markdown = source;
+
+        let (format, front_matter) = read_block(source, &mut markdown)?;
+
+        let start = front_matter.offset_from(&source);
+        let end = start + front_matter.len();
+
+        let newline = if source[..start].ends_with("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+
+        Ok(Self {
+            head: &source[..start],
+            lines: front_matter
+                .split_inclusive('\n')
+                .map(str::to_string)
+                .collect(),
+            tail: &source[end..],
+            newline,
+            format,
+        })
+    }
+
+    /// Returns the value of the key, if present and supported.
+    ///
+    /// See [`FieldValue::infer`] for the conversion of the value.
+    pub fn get(&self, key: &str) -> Option<FieldValue> {
+        let range = self.entry(key)?;
+
+        let entry = self.lines[range].concat();
+
+        let value = match self.format {
+            Format::Yaml => {
+                let mut docs = YamlLoader::load_from_str(&entry).ok()?;
+
+                if docs.is_empty() {
+                    return None;
+                }
+
+                docs.swap_remove(0)
+                    .into_hash()?
+                    .remove(&Yaml::from_str(key))?
+            }
+            Format::Toml => {
+                let (_, value) = DeTable::parse(&entry)
+                    .ok()?
+                    .into_inner()
+                    .into_iter()
+                    .find(|(name, _)| name.get_ref() == key)?;
+
+                toml_to_yaml(value.into_inner())
+            }
+        };
+
+        FieldValue::infer(value)
+    }
+
+    /// Sets the value of the key, keeping its position or adding it at the end.
+    ///
+    /// The trailing comment of a value on a single line is kept.
+    pub fn set(&mut self, key: &str, value: &FieldValue) {
+        let mut line = match self.format {
+            Format::Yaml => format!("{key}: {}", to_yaml(value)),
+            Format::Toml => format!("{key} = {}", to_toml(value)),
+        };
+
+        let Some(range) = self.entry(key) else {
+            let mut end = self.top_level_end();
+
+            // Keep the empty lines separating the tables.
+            while end > 0 && self.lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+
+            if let Some(last) = end.checked_sub(1).and_then(|i| self.lines.get_mut(i))
+                && !last.ends_with('\n')
+            {
+                last.push_str(self.newline);
+            }
+
+            line.push_str(self.newline);
+            self.lines.insert(end, line);
+
+            return;
+        };
+
+        if range.len() == 1
+            && let Some(comment) = trailing_comment(&self.lines[range.start])
+        {
+            line.push(' ');
+            line.push_str(comment);
+        }
+
+        line.push_str(self.newline);
+
+        self.lines.splice(range, [line]);
+    }
+
+    /// Removes the key, returns `true` if it was present.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let Some(range) = self.entry(key) else {
+            return false;
+        };
+
+        self.lines.drain(range);
+
+        true
+    }
+
+    /// Returns the syntax of the front matter.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the edited note.
+    pub fn finish(self) -> String {
+        let mut out = String::with_capacity(self.head.len() + self.tail.len());
+
+        out.push_str(self.head);
+        out.extend(self.lines);
+        out.push_str(self.tail);
+
+        out
+    }
+
+    /// Returns the lines of the key and its value.
+    fn entry(&self, key: &str) -> Option<Range<usize>> {
+        let start = self.lines[..self.top_level_end()]
+            .iter()
+            .position(|line| self.top_level_key(line) == Some(key))?;
+
+        let mut end = start + 1;
+
+        while let Some(line) = self.lines.get(end)
+            && self.is_continuation(&self.lines[end..], line)
+        {
+            end += 1;
+        }
+
+        Some(start..end)
+    }
+
+    /// Returns the index of the first TOML table header, after the top level keys.
+    fn top_level_end(&self) -> usize {
+        match self.format {
+            Format::Yaml => self.lines.len(),
+            Format::Toml => self
+                .lines
+                .iter()
+                .position(|line| line.starts_with('['))
+                .unwrap_or(self.lines.len()),
+        }
+    }
+
+    /// Returns the key of the line, if it starts a top level entry.
+    fn top_level_key<'l>(&self, line: &'l str) -> Option<&'l str> {
+        if line.starts_with(|c: char| c.is_whitespace() || c == '#' || c == '-') {
+            return None;
+        }
+
+        let key = match self.format {
+            Format::Yaml => {
+                let (key, rest) = line.split_once(':')?;
+
+                if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+                    return None;
+                }
+
+                key
+            }
+            Format::Toml => line.split_once('=')?.0,
+        };
+
+        Some(key.trim().trim_matches(['"', '\'']))
+    }
+
+    /// Checks if the line is part of the value of the previous key.
+    ///
+    /// Empty lines are part of the value only if followed by other lines of the value, like in
+    /// a block scalar.
+    fn is_continuation(&self, rest: &[String], line: &str) -> bool {
+        if line.trim().is_empty() {
+            return rest
+                .iter()
+                .find(|line| !line.trim().is_empty())
+                .is_some_and(|line| self.is_continuation(&[], line));
+        }
+
+        match self.format {
+            Format::Yaml => {
+                line.starts_with(char::is_whitespace)
+                    || line.starts_with("- ")
+                    || line.trim_end() == "-"
+            }
+            // Lines of multi-line arrays and strings.
+            Format::Toml => {
+                !line.trim_start().starts_with('#')
+                    && !line.starts_with('[')
+                    && self.top_level_key(line).is_none()
+            }
+        }
+    }
+}
+
+/// Returns the comment at the end of the line, skipping the quoted strings.
+fn trailing_comment(line: &str) -> Option<&str> {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut prev = ' ';
+
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            // Quotes in the middle of a plain scalar are literal.
+            None if matches!(c, '"' | '\'') && matches!(prev, ' ' | ':' | '[' | ',') => {
+                quote = Some(c);
+            }
+            None if c == '#' && prev.is_whitespace() => return Some(line[i..].trim_end()),
+            None => {}
+        }
+
+        prev = c;
+    }
+
+    None
+}
+
+/// Formats the value in the style of the note template.
+fn to_yaml(value: &FieldValue) -> String {
+    match value {
+        FieldValue::String(value) => quote(value),
+        FieldValue::Date(date) => format!(r#""{date}""#),
+        FieldValue::List(items) => {
+            let items: Vec<String> = items.iter().map(|item| quote(item)).collect();
+
+            format!("[{}]", items.join(", "))
+        }
+        FieldValue::Bool(value) => value.to_string(),
+    }
+}
+
+/// Formats the value like a Hugo front matter, with the dates as TOML local dates.
+fn to_toml(value: &FieldValue) -> String {
+    match value {
+        FieldValue::Date(date) => date.to_string(),
+        value => to_yaml(value),
+    }
+}
+
+/// Formats a double quoted YAML string, also valid in TOML.
+fn quote(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+
+    out.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str(r#"\""#),
+            '\\' => out.push_str(r"\\"),
+            '\n' => out.push_str(r"\n"),
+            '\t' => out.push_str(r"\t"),
+            '\r' => out.push_str(r"\r"),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    const NOTE: &str = r#"---
+title: "foo" # the title
+# comment
+description: "bar"
+tags:
+  - "a"
+
+  - "b"
+created: "1970-01-01"
+---
+
+# Hello   world
+"#;
+
+    #[test]
+    fn should_keep_the_note() {
+        let editor = FrontMatterEditor::new(NOTE).unwrap();
+
+        assert_eq!(editor.finish(), NOTE);
+    }
+
+    #[test]
+    fn should_edit_keys() {
+        let mut editor = FrontMatterEditor::new(NOTE).unwrap();
+
+        assert_eq!(
+            editor.get("tags"),
+            Some(FieldValue::List(vec!["a".to_string(), "b".to_string()]))
+        );
+
+        editor.set(
+            "title",
+            &FieldValue::String(r#"a "quoted" title"#.to_string()),
+        );
+        editor.set("tags", &FieldValue::List(vec!["c".to_string()]));
+        editor.set(
+            "updated",
+            &FieldValue::Date(NaiveDate::from_ymd_opt(1970, 1, 2).unwrap()),
+        );
+        assert!(editor.remove("description"));
+        assert!(!editor.remove("description"));
+
+        assert_eq!(
+            editor.finish(),
+            r#"---
+title: "a \"quoted\" title" # the title
+# comment
+tags: ["c"]
+created: "1970-01-01"
+updated: "1970-01-02"
+---
+
+# Hello   world
+"#
+        );
+    }
+
+    #[test]
+    fn should_keep_crlf() {
+        let note = "---\r\ntitle: \"foo\"\r\n---\r\nbody\r\n";
+
+        let mut editor = FrontMatterEditor::new(note).unwrap();
+
+        editor.set("released", &FieldValue::Bool(true));
+
+        assert_eq!(
+            editor.finish(),
+            "---\r\ntitle: \"foo\"\r\nreleased: true\r\n---\r\nbody\r\n"
+        );
+    }
+
+    #[test]
+    fn should_edit_toml_keys() {
+        let note = r#"+++
+title = "foo" # the title
+tags = [
+  "a",
+  "b",
+]
+
+[extra]
+updated = "nested"
++++
+body
+"#;
+
+        let mut editor = FrontMatterEditor::new(note).unwrap();
+
+        assert_eq!(
+            editor.get("tags"),
+            Some(FieldValue::List(vec!["a".to_string(), "b".to_string()]))
+        );
+        assert_eq!(editor.get("updated"), None);
+
+        editor.set("tags", &FieldValue::List(Vec::new()));
+        editor.set(
+            "updated",
+            &FieldValue::Date(NaiveDate::from_ymd_opt(1970, 1, 2).unwrap()),
+        );
+
+        assert_eq!(
+            editor.finish(),
+            r#"+++
+title = "foo" # the title
+tags = []
+updated = 1970-01-02
+
+[extra]
+updated = "nested"
++++
+body
+"#
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use std::{io, path::PathBuf};
+use std::{fmt::Display, io, path::PathBuf};
+
+use super::FrontMatterError;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -12,6 +14,137 @@ pub enum Error {
     InvalidPath(PathBuf),
     #[error("missing or incomplete frontmatter")]
     MissingFrontmatter,
-    #[error("couldn't parse front-mattter: {0}")]
-    FrontMatter(winnow::error::ContextError),
+    #[error("couldn't parse front-matter at {position}")]
+    FrontMatter {
+        error: winnow::error::ContextError,
+        position: Position,
+    },
+    #[error("invalid front-matter")]
+    Invalid(#[from] FrontMatterError),
+}
+
+impl Error {
+    /// Returns the position of the error in the note, if known.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Error::FrontMatter { position, .. } => Some(*position),
+            Error::Invalid(error) => error.position(),
+            Error::File(_)
+            | Error::ToJson(_)
+            | Error::Date(_)
+            | Error::InvalidPath(_)
+            | Error::MissingFrontmatter => None,
+        }
+    }
+
+    /// Returns a displayable report of the error, with the line of the note it refers to.
+    pub fn report<'a>(&'a self, source: &'a str) -> Report<'a> {
+        Report {
+            error: self,
+            position: self.position(),
+            source,
+        }
+    }
+}
+
+/// Location in the source of a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Byte offset from the start of the note.
+    pub offset: usize,
+    /// Line, starting from 1.
+    pub line: usize,
+    /// Column in characters, starting from 1.
+    pub column: usize,
+}
+
+impl Position {
+    /// Computes the line and column of the byte offset in the source.
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let offset = offset.min(source.len());
+        let before = source
+            .get(..offset)
+            .unwrap_or_else(|| &source[..source.floor_char_boundary(offset)]);
+
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+
+        Self {
+            offset,
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Error with all its sources and the [`snippet`] of the source it refers to.
+#[derive(Debug)]
+pub struct Report<'a> {
+    error: &'a dyn std::error::Error,
+    position: Option<Position>,
+    source: &'a str,
+}
+
+impl<'a> Report<'a> {
+    pub fn new(
+        error: &'a dyn std::error::Error,
+        position: Option<Position>,
+        source: &'a str,
+    ) -> Self {
+        Self {
+            error,
+            position,
+            source,
+        }
+    }
+}
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)?;
+
+        let mut source = self.error.source();
+        while let Some(err) = source {
+            write!(f, ": {err}")?;
+
+            source = err.source();
+        }
+
+        let Some(snippet) = self.position.and_then(|pos| snippet(self.source, pos)) else {
+            return Ok(());
+        };
+
+        write!(f, "\n{snippet}")
+    }
+}
+
+/// Returns the line of the source at the position, with a marker under the column.
+///
+/// ```text
+///  --> 4:10
+///   |
+/// 4 | created: 2025/01/31
+///   |          ^
+/// ```
+pub fn snippet(source: &str, position: Position) -> Option<String> {
+    let line = source.lines().nth(position.line.checked_sub(1)?)?;
+
+    let number = position.line.to_string();
+    let pad = " ".repeat(number.len());
+    let indent: String = line
+        .chars()
+        .take(position.column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    Some(format!(
+        "{pad}--> {position}\n{pad} |\n{number} | {line}\n{pad} | {indent}^"
+    ))
 }
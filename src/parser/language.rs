@@ -0,0 +1,235 @@
+//! BCP-47 language tags of the notes.
+
+use std::{fmt::Display, str::FromStr};
+
+use tantivy::tokenizer::Language as Stemmer;
+
+/// Language tag, like `en`, `it-CH` or `zh-Hant-TW`.
+///
+/// The primary language is normalised to the ISO 639-1 code when one exists, so `eng` and `en`
+/// are the same language.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct Language {
+    /// ISO 639-1 or 639-3 code, lowercase.
+    primary: String,
+    /// ISO 15924 code, title case.
+    script: Option<String>,
+    /// ISO 3166-1 or UN M.49 code, uppercase.
+    region: Option<String>,
+    /// Variants and extensions, lowercase.
+    rest: Vec<String>,
+}
+
+/// ISO 639-2 and 639-3 codes of the languages with an ISO 639-1 code.
+const THREE_LETTER_CODES: &[(&str, &str)] = &[
+    ("ara", "ar"),
+    ("ces", "cs"),
+    ("chi", "zh"),
+    ("cze", "cs"),
+    ("dan", "da"),
+    ("deu", "de"),
+    ("dut", "nl"),
+    ("ell", "el"),
+    ("eng", "en"),
+    ("fin", "fi"),
+    ("fra", "fr"),
+    ("fre", "fr"),
+    ("ger", "de"),
+    ("gre", "el"),
+    ("heb", "he"),
+    ("hin", "hi"),
+    ("hun", "hu"),
+    ("ita", "it"),
+    ("jpn", "ja"),
+    ("kor", "ko"),
+    ("nld", "nl"),
+    ("nob", "nb"),
+    ("nno", "nn"),
+    ("nor", "no"),
+    ("pol", "pl"),
+    ("por", "pt"),
+    ("ron", "ro"),
+    ("rum", "ro"),
+    ("rus", "ru"),
+    ("spa", "es"),
+    ("swe", "sv"),
+    ("tam", "ta"),
+    ("tur", "tr"),
+    ("ukr", "uk"),
+    ("zho", "zh"),
+];
+
+/// Languages with a Tantivy stemmer, by ISO 639-1 code.
+pub const STEMMERS: [(&str, Stemmer); 18] = [
+    ("ar", Stemmer::Arabic),
+    ("da", Stemmer::Danish),
+    ("nl", Stemmer::Dutch),
+    ("en", Stemmer::English),
+    ("fi", Stemmer::Finnish),
+    ("fr", Stemmer::French),
+    ("de", Stemmer::German),
+    ("el", Stemmer::Greek),
+    ("hu", Stemmer::Hungarian),
+    ("it", Stemmer::Italian),
+    ("no", Stemmer::Norwegian),
+    ("pt", Stemmer::Portuguese),
+    ("ro", Stemmer::Romanian),
+    ("ru", Stemmer::Russian),
+    ("es", Stemmer::Spanish),
+    ("sv", Stemmer::Swedish),
+    ("ta", Stemmer::Tamil),
+    ("tr", Stemmer::Turkish),
+];
+
+impl Language {
+    /// Primary language subtag, like `en`.
+    pub fn primary(&self) -> &str {
+        &self.primary
+    }
+
+    /// Region subtag, like `GB` for `en-GB`.
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// Returns the Tantivy stemmer for the language, if there is one.
+    pub fn stemmer(&self) -> Option<Stemmer> {
+        // Bokmål and Nynorsk share the Norwegian stemmer.
+        let code = match self.primary.as_str() {
+            "nb" | "nn" => "no",
+            code => code,
+        };
+
+        STEMMERS
+            .iter()
+            .find_map(|(stemmed, stemmer)| (*stemmed == code).then_some(*stemmer))
+    }
+}
+
+impl FromStr for Language {
+    type Err = LanguageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || LanguageError(s.to_string());
+
+        let mut subtags = s.split(['-', '_']);
+
+        let primary = subtags
+            .next()
+            .filter(|tag| {
+                (2..=3).contains(&tag.len()) && tag.chars().all(|c| c.is_ascii_alphabetic())
+            })
+            .ok_or_else(err)?
+            .to_ascii_lowercase();
+
+        let primary = THREE_LETTER_CODES
+            .iter()
+            .find_map(|(code, short)| (*code == primary).then(|| short.to_string()))
+            .unwrap_or(primary);
+
+        let mut language = Language {
+            primary,
+            script: None,
+            region: None,
+            rest: Vec::new(),
+        };
+
+        for tag in subtags {
+            if tag.is_empty() || tag.len() > 8 || !tag.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(err());
+            }
+
+            let alphabetic = tag.chars().all(|c| c.is_ascii_alphabetic());
+            let numeric = tag.chars().all(|c| c.is_ascii_digit());
+
+            match tag.len() {
+                4 if alphabetic
+                    && language.script.is_none()
+                    && language.region.is_none()
+                    && language.rest.is_empty() =>
+                {
+                    let (first, rest) = tag.split_at(1);
+
+                    language.script = Some(first.to_ascii_uppercase() + &rest.to_ascii_lowercase());
+                }
+                2 if alphabetic => {
+                    if language.region.is_some() || !language.rest.is_empty() {
+                        return Err(err());
+                    }
+
+                    language.region = Some(tag.to_ascii_uppercase());
+                }
+                3 if numeric && language.region.is_none() && language.rest.is_empty() => {
+                    language.region = Some(tag.to_string());
+                }
+                _ => language.rest.push(tag.to_ascii_lowercase()),
+            }
+        }
+
+        Ok(language)
+    }
+}
+
+impl TryFrom<String> for Language {
+    type Error = LanguageError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_str(&value)
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.primary)?;
+
+        for tag in self.script.iter().chain(&self.region).chain(&self.rest) {
+            write!(f, "-{tag}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid language {0}")]
+pub struct LanguageError(String);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_language_tags() {
+        let cases = [
+            ("en", "en"),
+            ("eng", "en"),
+            ("EN_gb", "en-GB"),
+            ("ita-CH", "it-CH"),
+            ("zh-hant-tw", "zh-Hant-TW"),
+            ("es-419", "es-419"),
+            ("gsw", "gsw"),
+            ("de-CH-1996", "de-CH-1996"),
+        ];
+
+        for (tag, expected) in cases {
+            let language = Language::from_str(tag).unwrap();
+
+            assert_eq!(language.to_string(), expected);
+        }
+
+        for invalid in ["", "e", "english", "en-", "en-GB-US", "en-toolongtag"] {
+            assert!(Language::from_str(invalid).is_err(), "{invalid}");
+        }
+
+        assert_eq!(
+            Language::from_str("eng").unwrap().stemmer(),
+            Some(Stemmer::English)
+        );
+        assert_eq!(
+            Language::from_str("nb-NO").unwrap().stemmer(),
+            Some(Stemmer::Norwegian)
+        );
+        assert_eq!(Language::from_str("ja").unwrap().stemmer(), None);
+    }
+}
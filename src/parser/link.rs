@@ -0,0 +1,124 @@
+//! Links between notes.
+
+use std::ops::Range;
+
+use pulldown_cmark::{Event, LinkType, Tag};
+
+/// Syntax of a link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// Wiki link like `[[note]]` or `[[note|text]]`.
+    Wiki,
+    /// Markdown link like `[text](note.md)`.
+    Markdown,
+}
+
+/// Outgoing link of a note.
+///
+/// Only links to local files are extracted, URLs with a scheme like `https:` or `mailto:` are
+/// ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub kind: LinkKind,
+    /// Linked path or note name, empty for links to a heading of the same note.
+    pub target: String,
+    /// Heading the link points to, without the `#`.
+    pub anchor: Option<String>,
+    /// Byte offset of the link in the file.
+    pub offset: usize,
+}
+
+impl Link {
+    fn from_tag(tag: &Tag<'_>, offset: usize) -> Option<Self> {
+        let Tag::Link {
+            link_type,
+            dest_url,
+            ..
+        } = tag
+        else {
+            return None;
+        };
+
+        let kind = match link_type {
+            LinkType::WikiLink { .. } => LinkKind::Wiki,
+            LinkType::Autolink | LinkType::Email => return None,
+            LinkType::Inline
+            | LinkType::Reference
+            | LinkType::ReferenceUnknown
+            | LinkType::Collapsed
+            | LinkType::CollapsedUnknown
+            | LinkType::Shortcut
+            | LinkType::ShortcutUnknown => LinkKind::Markdown,
+        };
+
+        if kind == LinkKind::Markdown && has_scheme(dest_url) {
+            return None;
+        }
+
+        let (target, anchor) = match dest_url.split_once('#') {
+            Some((target, anchor)) => (target, Some(percent_decode(anchor))),
+            None => (dest_url.as_ref(), None),
+        };
+
+        Some(Self {
+            kind,
+            target: percent_decode(target.trim()),
+            anchor,
+            offset,
+        })
+    }
+}
+
+/// Extracts the links from the events of the note body, which starts at `body_offset` in the
+/// file.
+pub(crate) fn extract<'a>(
+    events: impl IntoIterator<Item = &'a (Event<'a>, Range<usize>)>,
+    body_offset: usize,
+) -> Vec<Link> {
+    events
+        .into_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(tag) => Link::from_tag(tag, body_offset + range.start),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Checks for URLs like `https://...` or `mailto:...`.
+fn has_scheme(url: &str) -> bool {
+    let Some((scheme, _)) = url.split_once(':') else {
+        return false;
+    };
+
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Decodes the `%XX` escapes of a link destination, like `%20` for spaces.
+fn percent_decode(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8(bytes).unwrap_or_else(|_| value.to_string())
+}
@@ -1,23 +1,47 @@
-use std::{collections::HashSet, fmt::Write, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    str::FromStr,
+};
 
-use chrono::NaiveDate;
-use pulldown_cmark::{CodeBlockKind, Event, Options, Tag};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use indexmap::IndexMap;
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Tag, TagEnd};
+use tracing::debug;
+use winnow::stream::Offset;
 use winnow::{
     Parser,
     ascii::line_ending,
     combinator::{alt, delimited, eof},
     token::take_until,
 };
-use yaml_rust2::Yaml;
 
-use self::error::Error;
+use self::{
+    document::Document,
+    error::{Error, Position},
+    link::Link,
+    schema::{FieldValue, Schema},
+};
 
+mod document;
+pub mod editor;
 pub mod error;
+mod language;
+pub mod link;
+pub mod schema;
+
+pub use self::language::{Language, LanguageError, STEMMERS};
 
 #[derive(thiserror::Error, Debug)]
 pub enum FrontMatterError {
     #[error("failed to parse yaml")]
-    Yaml(#[from] yaml_rust2::ScanError),
+    Yaml {
+        #[source]
+        error: yaml_rust2::ScanError,
+        position: Position,
+    },
+    #[error("failed to parse toml: {message}")]
+    Toml { message: String, position: Position },
     #[error("yaml contains multiple documents")]
     MultipleDoc,
     #[error("empty front matter")]
@@ -25,29 +49,78 @@ pub enum FrontMatterError {
     #[error("expected and map")]
     Map,
     #[error("missing {0}")]
-    FieldMissing(&'static str),
+    FieldMissing(String),
     #[error("expected {name} to be a {expected}")]
     FieldType {
-        name: &'static str,
+        name: String,
         expected: &'static str,
+        position: Position,
     },
     #[error("invalid language")]
-    Language(#[from] LanguageError),
+    Language {
+        #[source]
+        error: LanguageError,
+        position: Position,
+    },
+    #[error("invalid value {value} for {name}, expected one of {}", expected.join(", "))]
+    Enum {
+        name: String,
+        value: String,
+        expected: Vec<String>,
+        position: Position,
+    },
     #[error("invalid date for {name}")]
     Date {
-        name: &'static str,
+        name: String,
+        position: Position,
         #[source]
         backtrace: chrono::ParseError,
     },
 }
 
 impl FrontMatterError {
-    const fn missing(missing: &'static str) -> Self {
-        Self::FieldMissing(missing)
+    fn missing(missing: &str) -> Self {
+        Self::FieldMissing(missing.to_string())
+    }
+
+    fn field_type(name: &str, expected: &'static str, position: Position) -> Self {
+        Self::FieldType {
+            name: name.to_string(),
+            expected,
+            position,
+        }
+    }
+
+    /// Returns the name of the invalid field, if the error is about a specific one.
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            FrontMatterError::FieldMissing(name)
+            | FrontMatterError::FieldType { name, .. }
+            | FrontMatterError::Enum { name, .. }
+            | FrontMatterError::Date { name, .. } => Some(name),
+            FrontMatterError::Language { .. } => Some("language"),
+            FrontMatterError::Yaml { .. }
+            | FrontMatterError::Toml { .. }
+            | FrontMatterError::MultipleDoc
+            | FrontMatterError::Empty
+            | FrontMatterError::Map => None,
+        }
     }
 
-    const fn field_type(name: &'static str, expected: &'static str) -> Self {
-        Self::FieldType { name, expected }
+    /// Returns the position of the error in the note, if known.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            FrontMatterError::Yaml { position, .. }
+            | FrontMatterError::Toml { position, .. }
+            | FrontMatterError::FieldType { position, .. }
+            | FrontMatterError::Enum { position, .. }
+            | FrontMatterError::Language { position, .. }
+            | FrontMatterError::Date { position, .. } => Some(*position),
+            FrontMatterError::FieldMissing(_)
+            | FrontMatterError::MultipleDoc
+            | FrontMatterError::Empty
+            | FrontMatterError::Map => None,
+        }
     }
 }
 
@@ -60,10 +133,80 @@ pub struct Markdown<'a> {
     pub updated: Option<NaiveDate>,
     pub released: bool,
     pub language: Option<Language>,
+    /// Fields declared in the [`Schema`] and the unknown ones, in this order.
+    pub extra: IndexMap<String, FieldValue>,
+    /// The note has no front matter, the metadata was inferred by [`parse_lenient`].
+    pub missing_metadata: bool,
+    pub links: Vec<Link>,
     pub content: Vec<Event<'a>>,
 }
 
 impl Markdown<'_> {
+    /// Returns the anchors of the headings.
+    ///
+    /// The anchor is the explicit `{#id}` of the heading, or the heading text converted to a
+    /// GitHub style slug, with a `-N` suffix for duplicates.
+    pub fn heading_ids(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        let mut seen = HashMap::<String, usize>::new();
+        let mut heading: Option<(Option<&str>, String)> = None;
+
+        for e in &self.content {
+            match e {
+                Event::Start(Tag::Heading { id, .. }) => {
+                    heading = Some((id.as_deref(), String::new()));
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some((_, title)) = &mut heading {
+                        title.push_str(text);
+                    }
+                }
+                Event::End(TagEnd::Heading(_)) => {
+                    let Some((id, title)) = heading.take() else {
+                        continue;
+                    };
+
+                    if let Some(id) = id {
+                        ids.push(id.to_string());
+
+                        continue;
+                    }
+
+                    let slug = slugify(&title);
+                    let count = seen.entry(slug.clone()).or_default();
+
+                    if *count == 0 {
+                        ids.push(slug);
+                    } else {
+                        ids.push(format!("{slug}-{count}"));
+                    }
+
+                    *count += 1;
+                }
+                _ => {}
+            }
+        }
+
+        ids
+    }
+
+    /// Returns the number of words in the text of the note.
+    pub fn word_count(&self) -> usize {
+        let mut text = String::new();
+
+        for e in &self.content {
+            match e {
+                Event::Text(t) | Event::Code(t) => text.push_str(t),
+                Event::Start(_) | Event::End(_) | Event::SoftBreak | Event::HardBreak => {
+                    text.push(' ');
+                }
+                _ => {}
+            }
+        }
+
+        text.split_whitespace().count()
+    }
+
     pub fn content_into_string(&self) -> String {
         let mut out = String::new();
 
@@ -89,6 +232,23 @@ impl Markdown<'_> {
     }
 }
 
+/// Converts the heading text to a GitHub style anchor.
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+
+    for c in title.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if c == '-' || c == '_' {
+            slug.push(c);
+        } else if c.is_whitespace() {
+            slug.push('-');
+        }
+    }
+
+    slug
+}
+
 fn write_tag(out: &mut String, tag: &Tag<'_>) {
     match tag {
         Tag::Paragraph => {}
@@ -168,149 +328,372 @@ pub struct FrontMatter {
     updated: Option<NaiveDate>,
     released: bool,
     language: Option<Language>,
+    extra: IndexMap<String, FieldValue>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub enum Language {
-    Eng,
-    It,
+/// Syntax of the front matter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// YAML between `---` lines.
+    Yaml,
+    /// TOML between `+++` lines, like in Hugo and Zola.
+    Toml,
 }
 
-impl FromStr for Language {
-    type Err = LanguageError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lang = match s {
-            "en" => Language::Eng,
-            "it" => Language::It,
-            invalid => return Err(LanguageError(invalid.to_string())),
-        };
-
-        Ok(lang)
+impl Format {
+    const fn delimiter(self) -> &'static str {
+        match self {
+            Format::Yaml => "---",
+            Format::Toml => "+++",
+        }
     }
 }
 
-#[derive(Debug, thiserror::Error)]
-#[error("invalid language {0}")]
-pub struct LanguageError(String);
+fn front_matter_block<'s>(markdown: &mut &'s str) -> winnow::Result<(Format, &'s str)> {
+    alt((delimited_block(Format::Yaml), delimited_block(Format::Toml))).parse_next(markdown)
+}
+
+fn delimited_block<'s>(
+    format: Format,
+) -> impl Parser<&'s str, (Format, &'s str), winnow::error::ContextError> {
+    let delimiter = format.delimiter();
 
-fn front_matter(markdown: &mut &str) -> winnow::Result<FrontMatter> {
     delimited(
-        ("---", line_ending),
-        take_until(1.., "---").try_map(parse_front_matter),
-        ("---", alt((line_ending, eof))),
+        (delimiter, line_ending),
+        take_until(1.., delimiter),
+        (delimiter, alt((line_ending, eof))),
     )
-    .parse_next(markdown)
+    .map(move |block| (format, block))
 }
 
-fn parse_front_matter(source: &str) -> Result<FrontMatter, FrontMatterError> {
-    let mut scan = yaml_rust2::YamlLoader::load_from_str(source)?;
-
-    if scan.is_empty() {
-        return Err(FrontMatterError::Empty);
+/// Parses the front matter `source` found in the `note`.
+fn parse_front_matter(
+    note: &str,
+    source: &str,
+    format: Format,
+    schema: &Schema,
+) -> Result<FrontMatter, FrontMatterError> {
+    let mut errors = Vec::new();
+
+    match read_front_matter(note, source, format, schema, &mut errors) {
+        Some(front_matter) => Ok(front_matter),
+        None => Err(errors.swap_remove(0)),
     }
+}
+
+/// Reads all the fields of the front matter, pushing an error for each invalid one.
+///
+/// Returns [`None`] if there is at least an error.
+fn read_front_matter(
+    note: &str,
+    source: &str,
+    format: Format,
+    schema: &Schema,
+    errors: &mut Vec<FrontMatterError>,
+) -> Option<FrontMatter> {
+    let mut doc = match Document::load(note, source.offset_from(&note), source, format) {
+        Ok(doc) => doc,
+        Err(err) => {
+            errors.push(err);
+
+            return None;
+        }
+    };
 
-    if scan.len() > 1 && scan[1] != Yaml::Null {
-        return Err(FrontMatterError::MultipleDoc);
+    if format == Format::Toml {
+        // Names of the fields in Hugo and Zola.
+        doc.rename("date", "created");
+        doc.rename("lastmod", "updated");
     }
 
-    let doc = scan.swap_remove(0);
+    let title = push_err(errors, read_string(&mut doc, "title"));
+    let description = push_err(
+        errors,
+        read_string(&mut doc, "description").or_else(|err| match err {
+            // Optional in Hugo and Zola.
+            FrontMatterError::FieldMissing(_) if format == Format::Toml => Ok(String::new()),
+            err => Err(err),
+        }),
+    );
+    let tags = push_err(errors, read_tags(&mut doc));
+    let created = push_err(
+        errors,
+        read_date(&mut doc, "created")
+            .and_then(|created| created.ok_or(FrontMatterError::missing("created"))),
+    );
+    let updated = push_err(errors, read_date(&mut doc, "updated"));
+    let released = push_err(errors, read_released(&mut doc, format));
+    let language = push_err(errors, read_language(&mut doc));
+    let extra = push_err(errors, read_extra(doc, schema));
+
+    Some(FrontMatter {
+        title: title?,
+        description: description?,
+        tags: tags?,
+        created: created?,
+        updated: updated?,
+        released: released?,
+        language: language?,
+        extra: extra?,
+    })
+}
 
-    let mut map = doc.into_hash().ok_or(FrontMatterError::Map)?;
+fn push_err<T>(
+    errors: &mut Vec<FrontMatterError>,
+    result: Result<T, FrontMatterError>,
+) -> Option<T> {
+    result.map_err(|err| errors.push(err)).ok()
+}
 
-    let title = map
-        .remove(&Yaml::from_str("title"))
-        .ok_or(FrontMatterError::missing("title"))?
-        .into_string()
-        .ok_or(FrontMatterError::field_type("title", "string"))?;
+fn read_string(doc: &mut Document, name: &str) -> Result<String, FrontMatterError> {
+    let (value, position) = doc.remove(name).ok_or(FrontMatterError::missing(name))?;
 
-    let description = map
-        .remove(&Yaml::from_str("description"))
-        .ok_or(FrontMatterError::missing("description"))?
+    value
         .into_string()
-        .ok_or(FrontMatterError::field_type("description", "string"))?;
-
-    let tags = match map.remove(&Yaml::from_str("tags")) {
-        Some(tags) => {
-            let tags_array = tags
-                .into_vec()
-                .ok_or(FrontMatterError::field_type("tags", "array"))?;
-
-            tags_array
-                .into_iter()
-                .map(|v| {
-                    v.into_string()
-                        .ok_or(FrontMatterError::field_type("tags", "string"))
-                })
-                .collect::<Result<_, _>>()?
-        }
-        None => HashSet::new(),
+        .ok_or(FrontMatterError::field_type(name, "string", position))
+}
+
+fn read_tags(doc: &mut Document) -> Result<HashSet<String>, FrontMatterError> {
+    let Some((tags, position)) = doc.remove("tags") else {
+        return Ok(HashSet::new());
     };
 
-    let created = map
-        .get(&Yaml::from_str("created"))
-        .ok_or(FrontMatterError::missing("created"))?
-        .as_str()
-        .ok_or(FrontMatterError::field_type("created", "string"))
-        .and_then(|date| {
-            NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|err| FrontMatterError::Date {
-                name: "created",
-                backtrace: err,
-            })
-        })?;
+    let tags_array = tags
+        .into_vec()
+        .ok_or(FrontMatterError::field_type("tags", "array", position))?;
 
-    let updated = map
-        .remove(&Yaml::from_str("updated"))
-        .map(|updated| {
-            updated
-                .as_str()
-                .ok_or(FrontMatterError::field_type("created", "string"))
+    tags_array
+        .into_iter()
+        .map(|v| {
+            v.into_string()
+                .ok_or(FrontMatterError::field_type("tags", "string", position))
+        })
+        .collect()
+}
+
+fn read_date(doc: &mut Document, name: &str) -> Result<Option<NaiveDate>, FrontMatterError> {
+    doc.remove(name)
+        .map(|(date, position)| {
+            date.as_str()
+                .ok_or(FrontMatterError::field_type(name, "string", position))
                 .and_then(|date| {
-                    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|err| {
-                        FrontMatterError::Date {
-                            name: "updated",
-                            backtrace: err,
-                        }
+                    parse_date(date).map_err(|err| FrontMatterError::Date {
+                        name: name.to_string(),
+                        position,
+                        backtrace: err,
                     })
                 })
         })
-        .transpose()?;
-
-    let released = map
-        .get(&Yaml::from_str("released"))
-        .map(|released| {
-            released
-                .as_bool()
-                .ok_or(FrontMatterError::field_type("released", "bool"))
-        })
-        .transpose()?
-        .unwrap_or_default();
+        .transpose()
+}
+
+/// Parses a `Y-m-d` date, or the date of a RFC 3339 date and time like the TOML ones.
+fn parse_date(date: &str) -> Result<NaiveDate, chrono::ParseError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").or_else(|err| {
+        DateTime::parse_from_rfc3339(date)
+            .map(|date| date.date_naive())
+            .or_else(|_| NaiveDateTime::from_str(date).map(|date| date.date()))
+            .map_err(|_| err)
+    })
+}
+
+/// Reads the `released` field, or the opposite of the Hugo and Zola `draft` in a TOML note.
+fn read_released(doc: &mut Document, format: Format) -> Result<bool, FrontMatterError> {
+    let read_bool = |(value, position): (yaml_rust2::Yaml, Position), name| {
+        value
+            .as_bool()
+            .ok_or(FrontMatterError::field_type(name, "bool", position))
+    };
+
+    if let Some(released) = doc.remove("released") {
+        return read_bool(released, "released");
+    }
 
-    let language = map
-        .get(&Yaml::from_str("language"))
-        .map(|language| {
+    if format == Format::Toml
+        && let Some(draft) = doc.remove("draft")
+    {
+        return read_bool(draft, "draft").map(|draft| !draft);
+    }
+
+    Ok(false)
+}
+
+fn read_language(doc: &mut Document) -> Result<Option<Language>, FrontMatterError> {
+    doc.remove("language")
+        .map(|(language, position)| {
             language
                 .as_str()
-                .ok_or(FrontMatterError::field_type("language", "string"))
-                .and_then(|lang| Language::from_str(lang).map_err(FrontMatterError::Language))
+                .ok_or(FrontMatterError::field_type("language", "string", position))
+                .and_then(|lang| {
+                    Language::from_str(lang)
+                        .map_err(|error| FrontMatterError::Language { error, position })
+                })
         })
-        .transpose()?;
+        .transpose()
+}
 
-    Ok(FrontMatter {
-        title,
-        description,
-        tags,
-        created,
-        updated,
-        released,
-        language,
-    })
+/// Reads the fields declared in the schema, then the remaining ones.
+///
+/// Only the first invalid field is reported.
+fn read_extra(
+    mut doc: Document,
+    schema: &Schema,
+) -> Result<IndexMap<String, FieldValue>, FrontMatterError> {
+    let mut extra = IndexMap::new();
+
+    for (name, field) in schema.fields() {
+        let value = match doc.remove(name) {
+            Some((value, position)) => field.field_type.read(name, value, position)?,
+            None => match &field.default {
+                Some(default) => default.clone(),
+                None if field.required => return Err(FrontMatterError::missing(name)),
+                None => continue,
+            },
+        };
+
+        extra.insert(name.to_string(), value);
+    }
+
+    for (name, value) in doc.into_entries() {
+        match FieldValue::infer(value) {
+            Some(value) => {
+                extra.insert(name, value);
+            }
+            None => debug!("skipping unsupported value of field {name}"),
+        }
+    }
+
+    Ok(extra)
+}
+
+/// Reads the front matter block at the start of the note.
+fn read_block<'s>(source: &'s str, markdown: &mut &'s str) -> Result<(Format, &'s str), Error> {
+    front_matter_block
+        .parse_next(markdown)
+        .map_err(|error| Error::FrontMatter {
+            error,
+            position: Position::from_offset(source, markdown.offset_from(&source)),
+        })
 }
 
-pub fn parse(mut markdown: &str) -> Result<Markdown<'_>, Error> {
-    let metadata = front_matter
-        .parse_next(&mut markdown)
-        .map_err(Error::FrontMatter)?;
+/// Returns all the errors in the front matter of the note, instead of stopping at the first one.
+///
+/// Fails if the note doesn't start with a front matter block.
+pub fn check(source: &str, schema: &Schema) -> Result<Vec<FrontMatterError>, Error> {
+    let mut markdown = source;
+
+    let (format, front_matter) = read_block(source, &mut markdown)?;
+
+    let mut errors = Vec::new();
+
+    read_front_matter(source, front_matter, format, schema, &mut errors);
+
+    Ok(errors)
+}
+
+/// Parses the note, keeping all the fields not handled by the parser as they are.
+pub fn parse(source: &str) -> Result<Markdown<'_>, Error> {
+    parse_with(source, &Schema::default())
+}
+
+/// Parses the note, validating the additional fields with the schema.
+pub fn parse_with<'a>(source: &'a str, schema: &Schema) -> Result<Markdown<'a>, Error> {
+    let mut markdown = source;
+
+    let (format, front_matter) = read_block(source, &mut markdown)?;
+    let metadata = parse_front_matter(source, front_matter, format, schema)?;
+
+    Ok(parse_body(source, markdown, metadata, false))
+}
+
+/// File of a note, used to infer the metadata missing from the note.
+#[derive(Debug, Clone)]
+pub struct FileInfo<'a> {
+    /// Name of the file without the extension.
+    pub name: &'a str,
+    /// Date of the last modification of the file.
+    pub modified: NaiveDate,
+}
+
+/// Parses the note, even if it doesn't have a front matter.
+///
+/// Without a front matter, the title is the first level one heading or the file name, and the
+/// creation date is the modification date of the file. The note is marked with
+/// [`Markdown::missing_metadata`].
+///
+/// A note starting with a front matter delimiter must have a valid front matter block.
+pub fn parse_lenient<'a>(
+    source: &'a str,
+    file: &FileInfo<'_>,
+    schema: &Schema,
+) -> Result<Markdown<'a>, Error> {
+    let mut markdown = source;
+
+    match read_block(source, &mut markdown) {
+        Ok((format, front_matter)) => {
+            let metadata = parse_front_matter(source, front_matter, format, schema)?;
+
+            Ok(parse_body(source, markdown, metadata, false))
+        }
+        Err(Error::FrontMatter { .. })
+            if ![Format::Yaml, Format::Toml]
+                .iter()
+                .any(|format| source.starts_with(format.delimiter())) =>
+        {
+            let metadata = FrontMatter {
+                title: first_heading(source).unwrap_or_else(|| file.name.replace('_', " ")),
+                description: String::new(),
+                tags: HashSet::new(),
+                created: file.modified,
+                updated: None,
+                released: false,
+                language: None,
+                extra: schema
+                    .fields()
+                    .filter_map(|(name, field)| Some((name.to_string(), field.default.clone()?)))
+                    .collect(),
+            };
+
+            Ok(parse_body(source, source, metadata, true))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the text of the first level one heading.
+fn first_heading(source: &str) -> Option<String> {
+    let mut events = pulldown_cmark::Parser::new_ext(source, Options::all()).skip_while(|e| {
+        !matches!(
+            e,
+            Event::Start(Tag::Heading {
+                level: HeadingLevel::H1,
+                ..
+            })
+        )
+    });
+
+    events.next()?;
+
+    let title: String = events
+        .take_while(|e| !matches!(e, Event::End(TagEnd::Heading(_))))
+        .filter_map(|e| match e {
+            Event::Text(text) | Event::Code(text) => Some(text.into_string()),
+            _ => None,
+        })
+        .collect();
+
+    let title = title.trim();
+
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+fn parse_body<'a>(
+    source: &'a str,
+    markdown: &'a str,
+    metadata: FrontMatter,
+    missing_metadata: bool,
+) -> Markdown<'a> {
+    let body_offset = source.len() - markdown.len();
 
     let FrontMatter {
         title,
@@ -320,12 +703,17 @@ pub fn parse(mut markdown: &str) -> Result<Markdown<'_>, Error> {
         updated,
         released,
         language,
+        extra,
     } = metadata;
 
     let options = Options::all();
-    let parser = pulldown_cmark::Parser::new_ext(markdown, options);
+    let events: Vec<_> = pulldown_cmark::Parser::new_ext(markdown, options)
+        .into_offset_iter()
+        .collect();
 
-    Ok(Markdown {
+    let links = link::extract(&events, body_offset);
+
+    Markdown {
         title,
         description,
         tags,
@@ -333,14 +721,19 @@ pub fn parse(mut markdown: &str) -> Result<Markdown<'_>, Error> {
         updated,
         released,
         language,
-        content: parser.collect(),
-    })
+        extra,
+        missing_metadata,
+        links,
+        content: events.into_iter().map(|(event, _)| event).collect(),
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    use crate::parser::link::LinkKind;
+
     #[test]
     fn should_parse_front_matter() {
         let markdown = r#"---
@@ -356,7 +749,8 @@ language: en
 
 # Hello world"#;
 
-        let result = parse_front_matter(markdown).unwrap();
+        let result =
+            parse_front_matter(markdown, markdown, Format::Yaml, &Schema::default()).unwrap();
 
         assert_eq!(result.title, "foo");
         assert_eq!(result.description, "bar");
@@ -367,6 +761,204 @@ language: en
         assert_eq!(result.created, NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
         assert_eq!(result.updated, NaiveDate::from_ymd_opt(1970, 1, 1));
         assert!(!result.released);
-        assert_eq!(result.language, Some(Language::Eng));
+        assert_eq!(result.language, Language::from_str("en").ok());
+    }
+
+    #[test]
+    fn should_extract_links() {
+        let markdown = r#"---
+title: "foo"
+description: "bar"
+created: "1970-01-01"
+---
+
+See [[other note]], [[dir/note#Some heading|alias]] and [bar](../bar%20baz.md#top).
+Also [self](#intro), <https://example.com> and [site](https://example.com).
+"#;
+
+        let result = parse(markdown).unwrap();
+
+        let links: Vec<_> = result
+            .links
+            .iter()
+            .map(|link| (link.kind, link.target.as_str(), link.anchor.as_deref()))
+            .collect();
+
+        assert_eq!(
+            links,
+            [
+                (LinkKind::Wiki, "other note", None),
+                (LinkKind::Wiki, "dir/note", Some("Some heading")),
+                (LinkKind::Markdown, "../bar baz.md", Some("top")),
+                (LinkKind::Markdown, "", Some("intro")),
+            ]
+        );
+        assert!(markdown[result.links[0].offset..].starts_with("[[other note]]"));
+    }
+
+    #[test]
+    fn should_report_error_position() {
+        let markdown = r#"---
+title: "foo"
+description: "bar"
+created: 2025/01/31
+---
+"#;
+
+        let err = parse(markdown).unwrap_err();
+        let position = err.position().unwrap();
+
+        assert_eq!((position.line, position.column), (4, 10));
+        assert!(markdown[position.offset..].starts_with("2025/01/31"));
+        assert!(
+            err.report(markdown)
+                .to_string()
+                .ends_with("4 | created: 2025/01/31\n  |          ^")
+        );
+    }
+
+    #[test]
+    fn should_read_extra_fields() {
+        let markdown = r#"---
+title: "foo"
+description: "bar"
+created: "1970-01-01"
+due: "1970-01-02"
+aliases: ["baz"]
+---
+"#;
+
+        let schema: Schema = toml::from_str(
+            r#"
+            due = { type = "date", required = true }
+            status = { type = "enum", values = ["draft", "done"], default = "draft" }
+            "#,
+        )
+        .unwrap();
+
+        let result = parse_with(markdown, &schema).unwrap();
+
+        assert_eq!(
+            result.extra.into_iter().collect::<Vec<_>>(),
+            [
+                (
+                    "due".to_string(),
+                    FieldValue::Date(NaiveDate::from_ymd_opt(1970, 1, 2).unwrap())
+                ),
+                (
+                    "status".to_string(),
+                    FieldValue::String("draft".to_string())
+                ),
+                (
+                    "aliases".to_string(),
+                    FieldValue::List(vec!["baz".to_string()])
+                ),
+            ]
+        );
+
+        let schema: Schema =
+            toml::from_str(r#"status = { type = "enum", values = ["done"] }"#).unwrap();
+        let markdown = markdown.replace("aliases: [\"baz\"]", "status: draft");
+
+        let err = parse_with(&markdown, &schema).unwrap_err();
+
+        assert_eq!(
+            err.position().map(|position| position.line),
+            Some(6),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn should_parse_toml_front_matter() {
+        let markdown = r#"+++
+title = "foo"
+description = "bar"
+tags = ["tag1"]
+created = 1970-01-01
+draft = true
+
+[extra]
+toc = true
++++
+
+# Hello world"#;
+
+        let result = parse(markdown).unwrap();
+
+        assert_eq!(result.title, "foo");
+        assert_eq!(result.created, NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+        assert!(!result.released);
+
+        let err = parse(&markdown.replace("1970-01-01", "\"1970/01/01\"")).unwrap_err();
+
+        assert_eq!(
+            err.position().map(|position| position.line),
+            Some(5),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn should_parse_hugo_front_matter() {
+        let hugo = r#"+++
+title = "Hugo post"
+date = 2024-01-01T10:00:00+01:00
+lastmod = 2024-02-03
+draft = false
+tags = ["x"]
++++
+
+Body"#;
+
+        let result = parse(hugo).unwrap();
+
+        assert_eq!(result.title, "Hugo post");
+        assert_eq!(result.description, "");
+        assert_eq!(result.created, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(result.updated, NaiveDate::from_ymd_opt(2024, 2, 3));
+        assert!(result.released);
+        assert!(result.tags.contains("x"));
+
+        let zola = r#"+++
+title = "Zola post"
+date = 2024-01-01
+updated = 2024-02-03T08:00:00
+draft = true
++++
+"#;
+
+        let result = parse(zola).unwrap();
+
+        assert_eq!(result.created, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(result.updated, NaiveDate::from_ymd_opt(2024, 2, 3));
+        assert!(!result.released);
+    }
+
+    #[test]
+    fn should_parse_without_front_matter() {
+        let file = FileInfo {
+            name: "some_note",
+            modified: NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+        };
+
+        let schema = Schema::default();
+
+        let result = parse_lenient("Intro\n\n# The `real` title\n\nbody", &file, &schema).unwrap();
+
+        assert!(result.missing_metadata);
+        assert_eq!(result.title, "The real title");
+        assert_eq!(result.created, file.modified);
+
+        let result = parse_lenient("just text", &file, &schema).unwrap();
+
+        assert_eq!(result.title, "some note");
+
+        let unclosed = "---\ntitle: \"Foo\"\n\n# Body\n";
+
+        assert!(matches!(
+            parse_lenient(unclosed, &file, &schema),
+            Err(Error::FrontMatter { .. })
+        ));
     }
 }
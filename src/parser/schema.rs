@@ -0,0 +1,271 @@
+//! User defined fields of the front matter.
+//!
+//! The fields are declared in the configuration file, for example:
+//!
+//! ```toml
+//! [front_matter.status]
+//! type = "enum"
+//! values = ["draft", "review", "done"]
+//! default = "draft"
+//!
+//! [front_matter.due]
+//! type = "date"
+//! required = true
+//! ```
+
+use std::fmt::Display;
+
+use chrono::NaiveDate;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use yaml_rust2::Yaml;
+
+use super::{FrontMatterError, error::Position};
+
+/// Fields handled by the parser, they cannot be redefined.
+pub const BUILTIN_FIELDS: [&str; 7] = [
+    "title",
+    "description",
+    "tags",
+    "created",
+    "updated",
+    "released",
+    "language",
+];
+
+/// Additional fields of the front matter.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "IndexMap<String, FieldSchema>")]
+pub struct Schema {
+    fields: IndexMap<String, FieldSchema>,
+}
+
+impl Schema {
+    pub fn new(fields: IndexMap<String, FieldSchema>) -> Result<Self, SchemaError> {
+        if let Some(name) = fields
+            .keys()
+            .find(|name| BUILTIN_FIELDS.contains(&name.as_str()))
+        {
+            return Err(SchemaError::Builtin(name.clone()));
+        }
+
+        Ok(Self { fields })
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &FieldSchema)> {
+        self.fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field))
+    }
+}
+
+impl TryFrom<IndexMap<String, FieldSchema>> for Schema {
+    type Error = SchemaError;
+
+    fn try_from(value: IndexMap<String, FieldSchema>) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    #[error("the field {0} is already defined by the parser")]
+    Builtin(String),
+    #[error("invalid default value: {0}")]
+    Default(String),
+}
+
+/// Declaration of a field.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawFieldSchema")]
+pub struct FieldSchema {
+    pub field_type: FieldType,
+    pub required: bool,
+    /// Value used when the field is missing.
+    pub default: Option<FieldValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    /// Date in the `Y-m-d` format.
+    Date,
+    /// List of strings.
+    List,
+    Bool,
+    /// String with one of the values.
+    Enum {
+        values: Vec<String>,
+    },
+}
+
+impl FieldType {
+    fn name(&self) -> &'static str {
+        match self {
+            FieldType::String | FieldType::Enum { .. } => "string",
+            FieldType::Date => "date",
+            FieldType::List => "array",
+            FieldType::Bool => "bool",
+        }
+    }
+
+    /// Reads the value of the field from the front matter.
+    pub(crate) fn read(
+        &self,
+        name: &str,
+        value: Yaml,
+        position: Position,
+    ) -> Result<FieldValue, FrontMatterError> {
+        let type_err = || FrontMatterError::field_type(name, self.name(), position);
+
+        match self {
+            FieldType::String => value
+                .into_string()
+                .map(FieldValue::String)
+                .ok_or_else(type_err),
+            FieldType::Date => {
+                let date = value.into_string().ok_or_else(type_err)?;
+
+                NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                    .map(FieldValue::Date)
+                    .map_err(|backtrace| FrontMatterError::Date {
+                        name: name.to_string(),
+                        position,
+                        backtrace,
+                    })
+            }
+            FieldType::List => value
+                .into_vec()
+                .ok_or_else(type_err)?
+                .into_iter()
+                .map(|item| {
+                    item.into_string()
+                        .ok_or_else(|| FrontMatterError::field_type(name, "string", position))
+                })
+                .collect::<Result<_, _>>()
+                .map(FieldValue::List),
+            FieldType::Bool => value.as_bool().map(FieldValue::Bool).ok_or_else(type_err),
+            FieldType::Enum { values } => {
+                let value = value.into_string().ok_or_else(type_err)?;
+
+                if !values.contains(&value) {
+                    return Err(FrontMatterError::Enum {
+                        name: name.to_string(),
+                        value,
+                        expected: values.clone(),
+                        position,
+                    });
+                }
+
+                Ok(FieldValue::String(value))
+            }
+        }
+    }
+
+    /// Converts the default value from the configuration.
+    fn default_value(&self, value: RawValue) -> Result<FieldValue, SchemaError> {
+        let value = match (self, value) {
+            (FieldType::String, RawValue::String(value)) => FieldValue::String(value),
+            (FieldType::Date, RawValue::String(value)) => {
+                NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                    .map(FieldValue::Date)
+                    .map_err(|err| SchemaError::Default(format!("{value}: {err}")))?
+            }
+            (FieldType::List, RawValue::List(value)) => FieldValue::List(value),
+            (FieldType::Bool, RawValue::Bool(value)) => FieldValue::Bool(value),
+            (FieldType::Enum { values }, RawValue::String(value)) => {
+                if !values.contains(&value) {
+                    return Err(SchemaError::Default(format!(
+                        "{value} is not one of {}",
+                        values.join(", ")
+                    )));
+                }
+
+                FieldValue::String(value)
+            }
+            (field_type, _) => {
+                return Err(SchemaError::Default(format!(
+                    "expected a {}",
+                    field_type.name()
+                )));
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFieldSchema {
+    #[serde(flatten)]
+    field_type: FieldType,
+    #[serde(default)]
+    required: bool,
+    default: Option<RawValue>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawValue {
+    Bool(bool),
+    String(String),
+    List(Vec<String>),
+}
+
+impl TryFrom<RawFieldSchema> for FieldSchema {
+    type Error = SchemaError;
+
+    fn try_from(value: RawFieldSchema) -> Result<Self, Self::Error> {
+        let default = value
+            .default
+            .map(|default| value.field_type.default_value(default))
+            .transpose()?;
+
+        Ok(Self {
+            field_type: value.field_type,
+            required: value.required,
+            default,
+        })
+    }
+}
+
+/// Value of a field not handled by the parser.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    String(String),
+    Date(NaiveDate),
+    List(Vec<String>),
+    Bool(bool),
+}
+
+impl FieldValue {
+    /// Converts the value of a field not declared in the schema.
+    ///
+    /// Numbers are kept as strings, while nested maps are not supported.
+    pub(crate) fn infer(value: Yaml) -> Option<Self> {
+        match value {
+            Yaml::String(value) | Yaml::Real(value) => Some(FieldValue::String(value)),
+            Yaml::Integer(value) => Some(FieldValue::String(value.to_string())),
+            Yaml::Boolean(value) => Some(FieldValue::Bool(value)),
+            Yaml::Array(items) => items
+                .into_iter()
+                .map(Yaml::into_string)
+                .collect::<Option<_>>()
+                .map(FieldValue::List),
+            Yaml::Hash(_) | Yaml::Alias(_) | Yaml::Null | Yaml::BadValue => None,
+        }
+    }
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::String(value) => write!(f, "{value}"),
+            FieldValue::Date(date) => write!(f, "{date}"),
+            FieldValue::List(items) => write!(f, "{}", items.join(", ")),
+            FieldValue::Bool(value) => write!(f, "{value}"),
+        }
+    }
+}
@@ -0,0 +1,695 @@
+//! Persistent search index of the notes.
+//!
+//! The index is kept in the cache directory between runs and updated incrementally: a file is
+//! re-indexed only if its modification time and content hash changed since the last commit.
+//!
+//! A note that can't be parsed is stored without text, with the parse error, so it's not read
+//! again until it changes.
+//!
+//! Every commit records a hash of the front matter schema, all the notes are parsed again when
+//! the schema in the configuration changes.
+//!
+//! The text of a note with a known language is also indexed in fields analysed with the stemmer
+//! of the language, so a search for "running" also finds "run".
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use chrono::{Local, NaiveDate, NaiveTime};
+use color_eyre::eyre::{self, Context, OptionExt, ensure};
+use dirs::cache_dir;
+use note::parser::{
+    FileInfo, Language, STEMMERS, error::Report, parse_lenient, schema::Schema as FrontMatterSchema,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tantivy::{
+    DateTime, Index, IndexWriter, TantivyDocument, Term,
+    directory::MmapDirectory,
+    doc,
+    schema::{
+        FAST, Field, INDEXED, IndexRecordOption, STORED, STRING, Schema, SchemaBuilder, TEXT,
+        TextFieldIndexing, TextOptions, Value,
+    },
+    tokenizer::{
+        Language as Stemmer, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer as StemFilter,
+        TextAnalyzer,
+    },
+};
+use tracing::{debug, error, info, trace, warn};
+use walkdir::WalkDir;
+
+use crate::{
+    config::Config,
+    edit::FileHash,
+    list::{is_hidden, strip_note_prefix},
+};
+
+pub(crate) struct SchemaFields {
+    pub(crate) path: Field,
+    pub(crate) title: Field,
+    pub(crate) description: Field,
+    pub(crate) tag: Field,
+    pub(crate) created: Field,
+    pub(crate) updated: Field,
+    pub(crate) released: Field,
+    pub(crate) language: Field,
+    pub(crate) content: Field,
+    pub(crate) mtime: Field,
+    pub(crate) hash: Field,
+    /// Set for the notes that couldn't be parsed.
+    pub(crate) failed: Field,
+    /// Parse error of the note.
+    pub(crate) error: Field,
+    pub(crate) stemmed: Vec<StemmedFields>,
+}
+
+/// Text fields analysed with the stemmer of a language.
+pub(crate) struct StemmedFields {
+    pub(crate) stemmer: Stemmer,
+    pub(crate) title: Field,
+    pub(crate) description: Field,
+    pub(crate) content: Field,
+}
+
+impl SchemaFields {
+    /// Returns the fields searched by default, the plain and stemmed text.
+    pub(crate) fn text_fields(&self) -> Vec<Field> {
+        let stemmed = self
+            .stemmed
+            .iter()
+            .flat_map(|fields| [fields.title, fields.description, fields.content]);
+
+        [self.title, self.description, self.content]
+            .into_iter()
+            .chain(stemmed)
+            .collect()
+    }
+
+    /// Returns the stemmed fields for the language, if it has a stemmer.
+    pub(crate) fn stemmed(&self, language: &Language) -> Option<&StemmedFields> {
+        let stemmer = language.stemmer()?;
+
+        self.stemmed.iter().find(|fields| fields.stemmer == stemmer)
+    }
+
+    fn write(
+        &self,
+        writer: &IndexWriter,
+        file: &NoteFile,
+        schema: &FrontMatterSchema,
+    ) -> eyre::Result<()> {
+        let path = file.path.to_str().ok_or_eyre("invalid non utf-8 path")?;
+
+        let name = file
+            .path
+            .file_stem()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let modified =
+            chrono::DateTime::<Local>::from(UNIX_EPOCH + Duration::from_nanos(file.mtime));
+
+        let info = FileInfo {
+            name: &name,
+            modified: modified.date_naive(),
+        };
+
+        let markdown = match parse_lenient(&file.content, &info, schema) {
+            Ok(m) => m,
+            Err(err) => {
+                error!(
+                    "couldn't parse the file {}: {}",
+                    file.path.display(),
+                    err.report(&file.content)
+                );
+
+                let message = Report::new(&err, None, &file.content).to_string();
+
+                writer.add_document(doc! {
+                    self.path => path,
+                    self.mtime => file.mtime,
+                    self.hash => file.hash.as_slice(),
+                    self.failed => true,
+                    self.error => message,
+                })?;
+
+                return Ok(());
+            }
+        };
+
+        trace!("file: `{}` content: `{}`", path, file.content);
+
+        let content = markdown.content_into_string();
+
+        let mut doc = doc! {
+            self.path => path,
+            self.title => markdown.title,
+            self.description => markdown.description,
+            self.content => content.as_str(),
+            self.created => date_time(markdown.created),
+            self.released => markdown.released,
+            self.mtime => file.mtime,
+            self.hash => file.hash.as_slice(),
+        };
+
+        if let Some(updated) = markdown.updated {
+            doc.add_date(self.updated, date_time(updated));
+        }
+
+        if let Some(language) = &markdown.language {
+            doc.add_text(self.language, language.to_string());
+
+            if let Some(stemmed) = self.stemmed(language) {
+                doc.add_text(stemmed.title, &markdown.title);
+                doc.add_text(stemmed.description, &markdown.description);
+                doc.add_text(stemmed.content, &content);
+            }
+        }
+
+        let mut tags = Vec::from_iter(markdown.tags);
+        tags.sort();
+
+        for tag in tags {
+            doc.add_text(self.tag, tag);
+        }
+
+        writer.add_document(doc)?;
+
+        Ok(())
+    }
+
+    fn path_term(&self, path: &Path) -> eyre::Result<Term> {
+        let path = path.to_str().ok_or_eyre("invalid non utf-8 path")?;
+
+        Ok(Term::from_field_text(self.path, path))
+    }
+}
+
+pub(crate) fn build_schema() -> (Schema, SchemaFields) {
+    let mut schema_builder = SchemaBuilder::new();
+
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let path = schema_builder.add_text_field("path", STRING | STORED);
+    let description = schema_builder.add_text_field("description", TEXT | STORED);
+    let tag = schema_builder.add_text_field("tag", STRING | STORED);
+    let created = schema_builder.add_date_field("created", INDEXED | STORED | FAST);
+    let updated = schema_builder.add_date_field("updated", INDEXED | STORED | FAST);
+    let released = schema_builder.add_bool_field("released", INDEXED | STORED);
+    let language = schema_builder.add_text_field("language", STRING | STORED);
+    let content = schema_builder.add_text_field("content", TEXT | STORED);
+    let mtime = schema_builder.add_u64_field("mtime", STORED);
+    let hash = schema_builder.add_bytes_field("hash", STORED);
+    let failed = schema_builder.add_bool_field("failed", INDEXED);
+    let error = schema_builder.add_text_field("error", STORED);
+
+    let stemmed = STEMMERS
+        .into_iter()
+        .map(|(code, stemmer)| {
+            let options = TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(&tokenizer_name(code))
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            );
+
+            StemmedFields {
+                stemmer,
+                title: schema_builder.add_text_field(&format!("title_{code}"), options.clone()),
+                description: schema_builder
+                    .add_text_field(&format!("description_{code}"), options.clone()),
+                content: schema_builder.add_text_field(&format!("content_{code}"), options),
+            }
+        })
+        .collect();
+
+    (
+        schema_builder.build(),
+        SchemaFields {
+            title,
+            path,
+            description,
+            tag,
+            created,
+            updated,
+            released,
+            language,
+            content,
+            mtime,
+            hash,
+            failed,
+            error,
+            stemmed,
+        },
+    )
+}
+
+fn tokenizer_name(code: &str) -> String {
+    format!("stem_{code}")
+}
+
+/// Registers the analyzers of the stemmed fields, they are not persisted with the index.
+fn register_tokenizers(index: &Index) {
+    for (code, stemmer) in STEMMERS {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(RemoveLongFilter::limit(40))
+            .filter(LowerCaser)
+            .filter(StemFilter::new(stemmer))
+            .build();
+
+        index.tokenizers().register(&tokenizer_name(code), analyzer);
+    }
+}
+
+/// Dates are indexed at midnight UTC.
+fn date_time(date: NaiveDate) -> DateTime {
+    DateTime::from_timestamp_secs(date.and_time(NaiveTime::MIN).and_utc().timestamp())
+}
+
+/// Returns the directory the index is stored in.
+pub(crate) fn index_dir() -> eyre::Result<PathBuf> {
+    let mut cache_dir = cache_dir().ok_or_eyre("missing cache dir")?;
+
+    cache_dir.push("note");
+    cache_dir.push("index");
+
+    Ok(cache_dir)
+}
+
+/// Opens the index in the cache directory, creating it if missing.
+///
+/// An index created with a different schema is removed and created again.
+pub(crate) fn open_index(dir: &Path) -> eyre::Result<(Index, SchemaFields)> {
+    fs::create_dir_all(dir).wrap_err("failed to create cache directory")?;
+
+    let (schema, fields) = build_schema();
+
+    let mmap_dir = MmapDirectory::open(dir)?;
+
+    let index = match Index::open_or_create(mmap_dir, schema.clone()) {
+        Ok(index) => index,
+        Err(tantivy::TantivyError::SchemaError(err)) => {
+            warn!("recreating the index: {err}");
+
+            fs::remove_dir_all(dir).wrap_err("failed to remove the index")?;
+            fs::create_dir_all(dir).wrap_err("failed to create cache directory")?;
+
+            Index::create_in_dir(dir, schema)?
+        }
+        Err(err) => return Err(err).wrap_err("couldn't open the index"),
+    };
+
+    register_tokenizers(&index);
+
+    Ok((index, fields))
+}
+
+/// Data stored in every commit of the index.
+#[derive(Debug, Serialize, Deserialize)]
+struct CommitPayload {
+    /// Time of the commit in RFC 3339 format.
+    committed: String,
+    /// Hash of the front matter schema the notes were parsed with.
+    schema: Option<String>,
+}
+
+impl CommitPayload {
+    fn new(schema: &FrontMatterSchema) -> Self {
+        Self {
+            committed: Local::now().to_rfc3339(),
+            schema: Some(schema_hash(schema)),
+        }
+    }
+
+    /// Reads the payload of the last commit, the older ones contain only the time.
+    fn load(index: &Index) -> eyre::Result<Option<Self>> {
+        let payload = index.load_metas()?.payload.map(|payload| {
+            serde_json::from_str(&payload).unwrap_or(Self {
+                committed: payload,
+                schema: None,
+            })
+        });
+
+        Ok(payload)
+    }
+}
+
+/// Returns the hex encoded SHA-256 of the schema.
+fn schema_hash(schema: &FrontMatterSchema) -> String {
+    let digest = Sha256::digest(format!("{schema:?}"));
+
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Modification time, hash and parse error of an indexed file.
+#[derive(Debug)]
+struct Indexed {
+    mtime: u64,
+    hash: Vec<u8>,
+    error: Option<String>,
+}
+
+/// A note that needs to be (re-)indexed.
+#[derive(Debug)]
+pub(crate) struct NoteFile {
+    pub(crate) path: PathBuf,
+    mtime: u64,
+    hash: Vec<u8>,
+    content: String,
+}
+
+impl NoteFile {
+    fn read(path: PathBuf, mtime: u64) -> eyre::Result<Self> {
+        let file = fs::File::open(&path)
+            .wrap_err_with(|| format!("couldn't open file {}", path.display()))?;
+
+        let mut reader = FileHash::new(BufReader::new(file));
+        let mut content = String::new();
+
+        reader
+            .read_to_string(&mut content)
+            .wrap_err_with(|| format!("couldn't read file {}", path.display()))?;
+
+        Ok(Self {
+            path,
+            mtime,
+            hash: reader.digest(),
+            content,
+        })
+    }
+}
+
+/// Differences between the notes and the index.
+#[derive(Debug, Default)]
+pub(crate) struct Changes {
+    pub(crate) added: Vec<NoteFile>,
+    pub(crate) modified: Vec<NoteFile>,
+    pub(crate) removed: Vec<PathBuf>,
+    /// Files with a new modification time but the same content, only the time is updated.
+    pub(crate) touched: Vec<NoteFile>,
+    /// Indexed files that couldn't be parsed, with the error.
+    pub(crate) failed: Vec<(PathBuf, String)>,
+}
+
+impl Changes {
+    /// Checks if the content of the index is up to date.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+
+    fn stale(&self) -> usize {
+        self.added.len() + self.modified.len() + self.removed.len()
+    }
+
+    /// Compares the notes with the documents in the index.
+    ///
+    /// Files with the same modification time are considered unchanged, unless `hash_all` is set.
+    /// All the files are modified if the index was built with a different front matter schema.
+    pub(crate) fn read(
+        index: &Index,
+        fields: &SchemaFields,
+        config: &Config,
+        hash_all: bool,
+    ) -> eyre::Result<Self> {
+        let mut indexed = indexed_files(index, fields)?;
+        let mut changes = Self::default();
+
+        let schema = CommitPayload::load(index)?.and_then(|payload| payload.schema);
+        let schema_changed = schema.is_none_or(|hash| hash != schema_hash(&config.front_matter));
+
+        if schema_changed && !indexed.is_empty() {
+            debug!("the front matter schema changed since the last commit");
+        }
+
+        for path in walk_notes(&config.note_path)? {
+            let mtime = modified(&path)?;
+
+            let Some(prev) = indexed.remove(&path) else {
+                changes.added.push(NoteFile::read(path, mtime)?);
+
+                continue;
+            };
+
+            if schema_changed {
+                changes.modified.push(NoteFile::read(path, mtime)?);
+
+                continue;
+            }
+
+            if !hash_all && prev.mtime == mtime {
+                trace!("unchanged {}", path.display());
+
+                changes.failed.extend(prev.error.map(|error| (path, error)));
+
+                continue;
+            }
+
+            let file = NoteFile::read(path, mtime)?;
+
+            if file.hash == prev.hash {
+                trace!("touched but unchanged {}", file.path.display());
+
+                if let Some(error) = prev.error {
+                    changes.failed.push((file.path.clone(), error));
+                }
+
+                if prev.mtime != mtime {
+                    changes.touched.push(file);
+                }
+
+                continue;
+            }
+
+            changes.modified.push(file);
+        }
+
+        changes.removed = indexed.into_keys().collect();
+
+        Ok(changes)
+    }
+
+    /// Applies and commits the changes to the index, parsing the notes with the front matter
+    /// schema.
+    pub(crate) fn apply(
+        &self,
+        index: &Index,
+        fields: &SchemaFields,
+        schema: &FrontMatterSchema,
+    ) -> eyre::Result<()> {
+        if self.is_empty() && self.touched.is_empty() {
+            debug!("index is up to date");
+
+            return Ok(());
+        }
+
+        info!(
+            added = self.added.len(),
+            modified = self.modified.len(),
+            removed = self.removed.len(),
+            touched = self.touched.len(),
+            "updating the index"
+        );
+
+        let mut writer: IndexWriter = index.writer(100_000_000)?;
+
+        let rewritten = self.modified.iter().chain(&self.touched);
+
+        for path in self
+            .removed
+            .iter()
+            .chain(rewritten.clone().map(|file| &file.path))
+        {
+            writer.delete_term(fields.path_term(path)?);
+        }
+
+        for file in self.added.iter().chain(rewritten) {
+            fields.write(&writer, file, schema)?;
+        }
+
+        let mut commit = writer.prepare_commit()?;
+        commit.set_payload(&serde_json::to_string(&CommitPayload::new(schema))?);
+        commit.commit()?;
+
+        Ok(())
+    }
+
+    fn print(&self, note_path: &Path) -> eyre::Result<()> {
+        let files = self
+            .added
+            .iter()
+            .map(|file| ("added", &file.path))
+            .chain(self.modified.iter().map(|file| ("modified", &file.path)))
+            .chain(self.removed.iter().map(|path| ("removed", path)));
+
+        for (change, path) in files {
+            let path = strip_note_prefix(note_path, path)?;
+
+            println!("{change}\t{}", path.display());
+        }
+
+        Ok(())
+    }
+
+    /// Prints the indexed notes that couldn't be parsed, they are not stale.
+    fn print_failed(&self, note_path: &Path) -> eyre::Result<()> {
+        for (path, error) in &self.failed {
+            let path = strip_note_prefix(note_path, path)?;
+
+            println!("unparsable\t{}\t{error}", path.display());
+        }
+
+        Ok(())
+    }
+}
+
+/// Brings the index up to date with the notes.
+pub(crate) fn sync(index: &Index, fields: &SchemaFields, config: &Config) -> eyre::Result<()> {
+    Changes::read(index, fields, config, false)?.apply(index, fields, &config.front_matter)
+}
+
+/// Removes the index and indexes all the notes again.
+pub fn rebuild(config: &Config) -> eyre::Result<()> {
+    let dir = index_dir()?;
+
+    if dir.exists() {
+        fs::remove_dir_all(&dir).wrap_err("failed to remove the index")?;
+    }
+
+    let (index, fields) = open_index(&dir)?;
+
+    sync(&index, &fields, config)?;
+
+    let documents = index.reader()?.searcher().num_docs();
+
+    println!("indexed {documents} documents");
+
+    Ok(())
+}
+
+/// Prints the number of documents, the last commit and the stale files of the index.
+pub fn status(config: &Config) -> eyre::Result<()> {
+    let (index, fields) = open_index(&index_dir()?)?;
+
+    let documents = index.reader()?.searcher().num_docs();
+    let payload = CommitPayload::load(&index)?;
+    let changes = Changes::read(&index, &fields, config, false)?;
+
+    println!("documents\t{documents}");
+    println!(
+        "last commit\t{}",
+        payload
+            .as_ref()
+            .map_or("never", |payload| &payload.committed)
+    );
+    println!("stale files\t{}", changes.stale());
+    println!("unparsable files\t{}", changes.failed.len());
+
+    changes.print(&config.note_path)?;
+    changes.print_failed(&config.note_path)
+}
+
+/// Checks the integrity of the index files and that every note is indexed and up to date.
+pub fn verify(config: &Config) -> eyre::Result<()> {
+    let (index, fields) = open_index(&index_dir()?)?;
+
+    let corrupted = index
+        .validate_checksum()
+        .wrap_err("couldn't validate the index checksum")?;
+
+    for file in &corrupted {
+        println!("corrupted\t{}", file.display());
+    }
+
+    let changes = Changes::read(&index, &fields, config, true)?;
+
+    changes.print(&config.note_path)?;
+    changes.print_failed(&config.note_path)?;
+
+    ensure!(
+        corrupted.is_empty() && changes.is_empty(),
+        "the index is out of date or corrupted, run `note index rebuild`"
+    );
+
+    Ok(())
+}
+
+fn indexed_files(index: &Index, fields: &SchemaFields) -> eyre::Result<HashMap<PathBuf, Indexed>> {
+    let searcher = index.reader()?.searcher();
+
+    let mut files = HashMap::new();
+
+    for segment in searcher.segment_readers() {
+        let store = segment.get_store_reader(1)?;
+
+        for doc_id in segment.doc_ids_alive() {
+            let doc: TantivyDocument = store.get(doc_id)?;
+
+            let path = doc
+                .get_first(fields.path)
+                .and_then(|v| v.as_str())
+                .ok_or_eyre("indexed document without path")?;
+            let mtime = doc
+                .get_first(fields.mtime)
+                .and_then(|v| v.as_u64())
+                .unwrap_or_default();
+            let hash = doc
+                .get_first(fields.hash)
+                .and_then(|v| v.as_bytes())
+                .unwrap_or_default()
+                .to_vec();
+            let error = doc
+                .get_first(fields.error)
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+
+            files.insert(PathBuf::from(path), Indexed { mtime, hash, error });
+        }
+    }
+
+    Ok(files)
+}
+
+fn modified(path: &Path) -> eyre::Result<u64> {
+    let modified = fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .wrap_err_with(|| format!("couldn't read modification time of {}", path.display()))?;
+
+    let nanos = modified
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+
+    u64::try_from(nanos).wrap_err("modification time out of range")
+}
+
+/// Returns all the markdown files in the path, skipping hidden ones.
+pub(crate) fn walk_notes(path: &Path) -> eyre::Result<Vec<PathBuf>> {
+    debug!("reading {}", path.display());
+
+    ensure!(path.exists(), "path doesn't exits");
+
+    let iter = WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| !is_hidden(e));
+
+    let mut files = Vec::new();
+    for entry in iter {
+        let entry = entry?;
+
+        debug!("checking {}", entry.path().display());
+
+        // Filter for markdown files
+        if !(entry.path().extension().is_some_and(|ext| ext == "md") && entry.path().is_file()) {
+            debug!("skipping {}", entry.path().display());
+
+            continue;
+        }
+
+        files.push(entry.into_path());
+    }
+
+    Ok(files)
+}
@@ -0,0 +1,463 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, hash_map::Entry},
+    io::{self, BufWriter, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, Context, ensure};
+use note::parser::Language;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use tantivy::{
+    DocAddress, Score, Searcher, TantivyDocument, Term,
+    collector::TopDocs,
+    query::{BooleanQuery, Occur, QueryParser, TermQuery},
+    schema::{IndexRecordOption, Value},
+    snippet::{Snippet, SnippetGenerator, collapse_overlapped_ranges},
+    tokenizer::Language as Stemmer,
+};
+
+use tracing::info;
+
+use crate::{
+    cli::{Query, QueryFormat},
+    config::Config,
+    edit::open,
+    list::strip_note_prefix,
+    search::execute_with_input,
+};
+
+use self::index::{SchemaFields, index_dir, open_index, sync};
+
+pub(crate) mod index;
+
+/// A search result.
+///
+/// This is the schema of the objects printed by the `json` and `jsonl` formats.
+#[derive(Debug, Serialize)]
+struct Hit {
+    /// Path of the note relative to the notes directory.
+    path: PathBuf,
+    /// Title of the note.
+    title: String,
+    /// Description of the note, empty if missing.
+    description: String,
+    /// Tags of the note, sorted alphabetically.
+    tags: Vec<String>,
+    /// Relevance of the note for the query, higher is better.
+    score: Score,
+    /// Passage of the content matching the query, with the hits enclosed in `<mark>` and
+    /// `</mark>`, or null if the match is not in the content.
+    snippet: Option<String>,
+}
+
+impl Hit {
+    fn read(
+        doc: &TantivyDocument,
+        fields: &SchemaFields,
+        score: Score,
+        snippet: Option<String>,
+        config: &Config,
+    ) -> eyre::Result<Self> {
+        let text = |field| {
+            doc.get_first(field)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let path = PathBuf::from(text(fields.path));
+        let path = strip_note_prefix(&config.note_path, &path)?.to_owned();
+
+        let tags = doc
+            .get_all(fields.tag)
+            .filter_map(|v| v.as_str())
+            .map(str::to_string)
+            .collect();
+
+        Ok(Self {
+            path,
+            title: text(fields.title),
+            description: text(fields.description),
+            tags,
+            score,
+            snippet,
+        })
+    }
+
+    fn write_text(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{}: {}", self.path.display(), self.title)?;
+
+        if !self.description.is_empty() {
+            writeln!(out, "    {}", self.description)?;
+        }
+
+        if !self.tags.is_empty() {
+            writeln!(out, "    #{}", self.tags.join(" #"))?;
+        }
+
+        if let Some(snippet) = &self.snippet {
+            writeln!(out, "    > {snippet}")?;
+        }
+
+        Ok(())
+    }
+
+    fn write_tsv(&self, out: &mut impl Write) -> io::Result<()> {
+        let clean = |s: &str| s.replace(['\t', '\n', '\r'], " ");
+
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.score,
+            clean(&self.path.to_string_lossy()),
+            clean(&self.title),
+            clean(&self.description),
+            clean(&self.tags.join(",")),
+            clean(self.snippet.as_deref().unwrap_or_default())
+        )
+    }
+}
+
+/// Strings enclosing the highlighted terms of a snippet.
+#[derive(Debug, Clone, Copy)]
+struct Markers {
+    prefix: &'static str,
+    postfix: &'static str,
+}
+
+impl Markers {
+    const NONE: Self = Self {
+        prefix: "",
+        postfix: "",
+    };
+
+    const ANSI: Self = Self {
+        prefix: "\x1b[1;31m",
+        postfix: "\x1b[0m",
+    };
+
+    const MARK: Self = Self {
+        prefix: "<mark>",
+        postfix: "</mark>",
+    };
+
+    fn for_format(format: QueryFormat) -> Self {
+        match format {
+            QueryFormat::Text if io::stdout().is_terminal() => Self::ANSI,
+            QueryFormat::Json | QueryFormat::Jsonl => Self::MARK,
+            QueryFormat::Text | QueryFormat::Tsv | QueryFormat::Paths => Self::NONE,
+        }
+    }
+
+    /// Returns the snippet fragment on a single line, with the highlighted terms enclosed.
+    fn highlight(&self, snippet: &Snippet) -> Option<String> {
+        if snippet.is_empty() {
+            return None;
+        }
+
+        let fragment = snippet.fragment();
+
+        let mut out = String::new();
+        let mut start = 0;
+
+        for range in collapse_overlapped_ranges(snippet.highlighted()) {
+            out.push_str(&fragment[start..range.start]);
+            out.push_str(self.prefix);
+            out.push_str(&fragment[range.clone()]);
+            out.push_str(self.postfix);
+
+            start = range.end;
+        }
+
+        out.push_str(&fragment[start..]);
+
+        Some(out.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+}
+
+/// Converts the `Y-m-d` dates of the date fields to the RFC 3339 format expected by the query
+/// parser.
+///
+/// For example `created:[2025-01-01 TO 2025-06-30]` becomes
+/// `created:[2025-01-01T00:00:00Z TO 2025-06-30T00:00:00Z]`.
+fn expand_dates(search: &str) -> eyre::Result<Cow<'_, str>> {
+    let clause = Regex::new(r"\b(created|updated):([\[\{][^\]\}]*[\]\}]|\d{4}-\d{2}-\d{2}\b)")?;
+    let date = Regex::new(r"\b\d{4}-\d{2}-\d{2}\b")?;
+
+    let expanded = clause.replace_all(search, |caps: &Captures| {
+        let field = &caps[1];
+        let value = &caps[2];
+
+        if value.starts_with(['[', '{']) {
+            let range = date.replace_all(value, "${0}T00:00:00Z");
+
+            format!("{field}:{range}")
+        } else {
+            format!(r#"{field}:"{value}T00:00:00Z""#)
+        }
+    });
+
+    Ok(expanded)
+}
+
+pub fn query(args: &Query, config: &Config) -> eyre::Result<()> {
+    ensure!(args.limit > 0, "the limit must be greater than zero");
+
+    let (index, fields) = open_index(&index_dir()?)?;
+
+    sync(&index, &fields, config)?;
+
+    let reader = index.reader()?;
+
+    let searcher = reader.searcher();
+
+    let query_parser = QueryParser::for_index(&index, fields.text_fields());
+
+    // QueryParser may fail if the query is not in the right
+    // format. For user facing applications, this can be a problem.
+    // A ticket has been opened regarding this problem.
+    let query = query_parser.parse_query(&expand_dates(&args.search)?)?;
+
+    // Skip the notes that couldn't be parsed, they are indexed without text.
+    let filtered = BooleanQuery::new(vec![
+        (Occur::Must, query.box_clone()),
+        (
+            Occur::MustNot,
+            Box::new(TermQuery::new(
+                Term::from_field_bool(fields.failed, true),
+                IndexRecordOption::Basic,
+            )),
+        ),
+    ]);
+
+    // Perform search.
+    // `topdocs` contains the most relevant doc ids, sorted by decreasing scores...
+    let top_docs: Vec<(Score, DocAddress)> = searcher.search(
+        &filtered,
+        &TopDocs::with_limit(args.limit)
+            .and_offset(args.offset)
+            .order_by_score(),
+    )?;
+
+    let mut snippets = Snippets::new(&searcher, &*query, &fields)?;
+    let markers = Markers::for_format(args.format);
+
+    let hits = top_docs
+        .into_iter()
+        .map(|(score, doc_address)| {
+            // Retrieve the actual content of documents given its `doc_address`.
+            let doc = searcher.doc::<TantivyDocument>(doc_address)?;
+
+            let snippet = markers.highlight(&snippets.snippet(&doc)?);
+
+            Hit::read(&doc, &fields, score, snippet, config)
+        })
+        .collect::<eyre::Result<Vec<Hit>>>()?;
+
+    if args.open {
+        let Some(hit) = hits.into_iter().next() else {
+            info!("no note matches the query");
+
+            return Ok(());
+        };
+
+        return open(config, &hit.path, hit.title);
+    }
+
+    if args.pick {
+        let Some(hit) = pick_hit(config, hits)? else {
+            return Ok(());
+        };
+
+        return open(config, &hit.path, hit.title);
+    }
+
+    print_hits(args.format, &hits)
+}
+
+/// Snippet generators for the content, stemmed with the language of the note.
+struct Snippets<'a> {
+    searcher: &'a Searcher,
+    query: &'a dyn tantivy::query::Query,
+    fields: &'a SchemaFields,
+    plain: SnippetGenerator,
+    stemmed: HashMap<Stemmer, SnippetGenerator>,
+}
+
+impl<'a> Snippets<'a> {
+    fn new(
+        searcher: &'a Searcher,
+        query: &'a dyn tantivy::query::Query,
+        fields: &'a SchemaFields,
+    ) -> eyre::Result<Self> {
+        Ok(Self {
+            searcher,
+            query,
+            fields,
+            plain: SnippetGenerator::create(searcher, query, fields.content)?,
+            stemmed: HashMap::new(),
+        })
+    }
+
+    fn snippet(&mut self, doc: &TantivyDocument) -> eyre::Result<Snippet> {
+        let stemmed = doc
+            .get_first(self.fields.language)
+            .and_then(|v| v.as_str())
+            .and_then(|language| language.parse::<Language>().ok())
+            .and_then(|language| self.fields.stemmed(&language));
+
+        let Some(stemmed) = stemmed else {
+            return Ok(self.plain.snippet_from_doc(doc));
+        };
+
+        let generator = match self.stemmed.entry(stemmed.stemmer) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(SnippetGenerator::create(
+                self.searcher,
+                self.query,
+                stemmed.content,
+            )?),
+        };
+
+        let content = doc
+            .get_first(self.fields.content)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+
+        Ok(generator.snippet(content))
+    }
+}
+
+fn print_hits(format: QueryFormat, hits: &[Hit]) -> eyre::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    match format {
+        QueryFormat::Text => {
+            for (i, hit) in hits.iter().enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+
+                hit.write_text(&mut out)?;
+            }
+        }
+        QueryFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, hits)?;
+            writeln!(out)?;
+        }
+        QueryFormat::Jsonl => {
+            for hit in hits {
+                serde_json::to_writer(&mut out, hit)?;
+                writeln!(out)?;
+            }
+        }
+        QueryFormat::Tsv => {
+            for hit in hits {
+                hit.write_tsv(&mut out)?;
+            }
+        }
+        QueryFormat::Paths => {
+            for hit in hits {
+                writeln!(out, "{}", hit.path.display())?;
+            }
+        }
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Lets the user choose one of the hits.
+///
+/// The hits are piped to the configured pick command, one `path\ttitle` per line, and the path
+/// of the first line of its output is the chosen note. Without a command the hits are listed on
+/// the terminal and the number of the note is read from stdin.
+fn pick_hit(config: &Config, mut hits: Vec<Hit>) -> eyre::Result<Option<Hit>> {
+    if hits.is_empty() {
+        info!("no note matches the query");
+
+        return Ok(None);
+    }
+
+    if let Some(cmd) = &config.pick_command {
+        let input: String = hits
+            .iter()
+            .map(|hit| format!("{}\t{}\n", hit.path.display(), hit.title))
+            .collect();
+
+        let Some(output) = execute_with_input(config, cmd, &input)? else {
+            return Ok(None);
+        };
+
+        let line = output.lines().next().unwrap_or_default();
+        let path = Path::new(line.split('\t').next().unwrap_or_default().trim());
+
+        return hits
+            .into_iter()
+            .find(|hit| hit.path == path)
+            .map(Some)
+            .ok_or_else(|| eyre::eyre!("unknown note {}", path.display()));
+    }
+
+    let mut err = io::stderr().lock();
+
+    for (i, hit) in hits.iter().enumerate() {
+        writeln!(err, "{:>3}  {}: {}", i + 1, hit.path.display(), hit.title)?;
+    }
+
+    write!(err, "Open note [1-{}]: ", hits.len())?;
+    err.flush()?;
+
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let choice: usize = line
+        .parse()
+        .wrap_err_with(|| format!("invalid choice {line}"))?;
+
+    ensure!(
+        (1..=hits.len()).contains(&choice),
+        "choice must be between 1 and {}",
+        hits.len()
+    );
+
+    Ok(Some(hits.swap_remove(choice - 1)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_expand_dates() {
+        let cases = [
+            ("foo bar", "foo bar"),
+            ("created:2025-01-01", r#"created:"2025-01-01T00:00:00Z""#),
+            (
+                "tag:work AND created:[2025-01-01 TO 2025-06-30]",
+                "tag:work AND created:[2025-01-01T00:00:00Z TO 2025-06-30T00:00:00Z]",
+            ),
+            (
+                "updated:{2025-01-01 TO *}",
+                "updated:{2025-01-01T00:00:00Z TO *}",
+            ),
+            (
+                "created:[2025-01-01T10:00:00Z TO 2025-06-30T00:00:00Z]",
+                "created:[2025-01-01T10:00:00Z TO 2025-06-30T00:00:00Z]",
+            ),
+            ("2025-01-01", "2025-01-01"),
+        ];
+
+        for (search, exp) in cases {
+            assert_eq!(expand_dates(search).unwrap(), exp);
+        }
+    }
+}
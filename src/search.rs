@@ -1,8 +1,11 @@
-use std::process::{Command, Stdio};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
 
 use color_eyre::{
     Result,
-    eyre::{Context, ensure},
+    eyre::{Context, OptionExt, ensure},
 };
 use tracing::{debug, trace};
 
@@ -40,6 +43,50 @@ pub fn execute_command(config: &Config, cmd: &str, search: &str) -> Result<Optio
     Ok(Some(path))
 }
 
+/// Executes the command writing the input to its stdin.
+pub fn execute_with_input(config: &Config, cmd: &str, input: &str) -> Result<Option<String>> {
+    let mut child = Command::new(&config.shell)
+        .current_dir(&config.note_path)
+        .args(["-c", cmd])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("failed to execute pick command")?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_eyre("missing stdin of pick command")?;
+    stdin
+        .write_all(input.as_bytes())
+        .context("failed to write to pick command")?;
+    // Close stdin so the command sees the end of the input
+    drop(stdin);
+
+    let output = child
+        .wait_with_output()
+        .context("failed to execute pick command")?;
+
+    debug!("status: {}", &output.status);
+    debug!("stdout: {:?}", &output.stdout);
+
+    ensure!(
+        output.status.success(),
+        "command returned with status {}",
+        output.status
+    );
+
+    let path = String::from_utf8(output.stdout).context("invalid UTF-8 in command output")?;
+
+    if path.trim().is_empty() {
+        debug!("empty path");
+
+        return Ok(None);
+    }
+
+    Ok(Some(path))
+}
+
 pub fn find_file(config: &Config, file: &str) -> Result<()> {
     let Some(output) = execute_command(config, &config.find_command, file)? else {
         return Ok(());
@@ -47,7 +94,7 @@ pub fn find_file(config: &Config, file: &str) -> Result<()> {
 
     trace!("{}", output);
 
-    note(config, &output)
+    note(config, &output, None)
 }
 
 pub fn grep_content(config: &Config, search: &str) -> Result<()> {
@@ -57,5 +104,5 @@ pub fn grep_content(config: &Config, search: &str) -> Result<()> {
 
     trace!("{}", output);
 
-    note(config, &output)
+    note(config, &output, None)
 }
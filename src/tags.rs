@@ -0,0 +1,199 @@
+//! Listing and editing of the tags across the notes.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use color_eyre::eyre::{self, Context};
+use note::parser::{editor::FrontMatterEditor, schema::FieldValue};
+use tracing::{debug, warn};
+
+use crate::{config::Config, list::strip_note_prefix, query::index::walk_notes};
+
+/// Lines of context around the changes in the diff.
+const DIFF_CONTEXT: usize = 3;
+
+/// Prints every tag with the number of notes using it.
+pub fn list_tags(config: &Config) -> eyre::Result<()> {
+    let mut counts = BTreeMap::<String, usize>::new();
+
+    for path in walk_notes(&config.note_path)? {
+        let content = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("couldn't read file {}", path.display()))?;
+
+        let Some(mut tags) = read_tags(&path, &content) else {
+            continue;
+        };
+
+        tags.sort();
+        tags.dedup();
+
+        for tag in tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+
+    let mut out = io::stdout().lock();
+
+    for (tag, count) in counts {
+        writeln!(out, "{tag}\t{count}")?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the tags with the `into` one in all the notes.
+///
+/// Renaming a tag is merging it alone into the new one.
+pub fn merge_tags(config: &Config, tags: &[String], into: &str, dry_run: bool) -> eyre::Result<()> {
+    rewrite_tags(config, dry_run, |note_tags| {
+        // Keep the other notes as they are, even with duplicated tags.
+        if !note_tags.iter().any(|tag| tags.contains(tag)) {
+            return note_tags.to_vec();
+        }
+
+        let replaced = note_tags.iter().map(|tag| {
+            if tags.contains(tag) {
+                into.to_string()
+            } else {
+                tag.clone()
+            }
+        });
+
+        dedup(replaced)
+    })
+}
+
+/// Removes the tag from all the notes.
+pub fn delete_tag(config: &Config, tag: &str, dry_run: bool) -> eyre::Result<()> {
+    rewrite_tags(config, dry_run, |note_tags| {
+        note_tags.iter().filter(|t| *t != tag).cloned().collect()
+    })
+}
+
+/// Sets the tags of every note to the edited ones, printing the path of the modified notes or
+/// the diff of the changes.
+fn rewrite_tags(
+    config: &Config,
+    dry_run: bool,
+    edit: impl Fn(&[String]) -> Vec<String>,
+) -> eyre::Result<()> {
+    let mut out = io::stdout().lock();
+
+    for path in walk_notes(&config.note_path)? {
+        let content = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("couldn't read file {}", path.display()))?;
+
+        let Some(tags) = read_tags(&path, &content) else {
+            continue;
+        };
+
+        let edited = edit(&tags);
+
+        if edited == tags {
+            continue;
+        }
+
+        // Already checked by reading the tags.
+        let Ok(mut editor) = FrontMatterEditor::new(&content) else {
+            continue;
+        };
+
+        editor.set("tags", &FieldValue::List(edited));
+
+        let updated = editor.finish();
+        let rel_path = strip_note_prefix(&config.note_path, &path)?;
+
+        if dry_run {
+            write_diff(&mut out, rel_path, &content, &updated)?;
+
+            continue;
+        }
+
+        fs::write(&path, updated)
+            .wrap_err_with(|| format!("couldn't write file {}", path.display()))?;
+
+        writeln!(out, "{}", rel_path.display())?;
+    }
+
+    Ok(())
+}
+
+/// Reads the tags from the front matter of the note.
+fn read_tags(path: &Path, content: &str) -> Option<Vec<String>> {
+    let editor = FrontMatterEditor::new(content)
+        .inspect_err(|_| debug!("skipping {} without front matter", path.display()))
+        .ok()?;
+
+    match editor.get("tags") {
+        Some(FieldValue::List(tags)) => Some(tags),
+        None => Some(Vec::new()),
+        Some(_) => {
+            warn!("skipping {}, the tags are not a list", path.display());
+
+            None
+        }
+    }
+}
+
+/// Removes the duplicated tags, keeping the first one.
+fn dedup(tags: impl Iterator<Item = String>) -> Vec<String> {
+    let mut unique = Vec::new();
+
+    for tag in tags {
+        if !unique.contains(&tag) {
+            unique.push(tag);
+        }
+    }
+
+    unique
+}
+
+/// Writes the changed lines as a unified diff.
+///
+/// Only the front matter is edited, so the changes are in a single hunk.
+fn write_diff(out: &mut impl Write, path: &Path, old: &str, new: &str) -> io::Result<()> {
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let start = prefix.saturating_sub(DIFF_CONTEXT);
+    let old_end = old.len() - suffix;
+    let new_end = new.len() - suffix;
+    let context_end = (old_end + DIFF_CONTEXT).min(old.len());
+    let trailing = context_end - old_end;
+
+    writeln!(out, "--- a/{}", path.display())?;
+    writeln!(out, "+++ b/{}", path.display())?;
+    writeln!(
+        out,
+        "@@ -{},{} +{},{} @@",
+        start + 1,
+        context_end - start,
+        start + 1,
+        new_end + trailing - start
+    )?;
+
+    let lines = old[start..prefix]
+        .iter()
+        .map(|line| (' ', line))
+        .chain(old[prefix..old_end].iter().map(|line| ('-', line)))
+        .chain(new[prefix..new_end].iter().map(|line| ('+', line)))
+        .chain(old[old_end..context_end].iter().map(|line| (' ', line)));
+
+    for (marker, line) in lines {
+        writeln!(out, "{marker}{}", line.trim_end_matches('\n'))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+//! Templates of the new notes, read at runtime from the templates directories.
+//!
+//! A template is a markdown file with `{{ variable }}` placeholders, for example:
+//!
+//! ```markdown
+//! ---
+//! title: "{{ title }}"
+//! created: "{{ date }}"
+//! ---
+//!
+//! # {{ title }}
+//!
+//! [Previous]({{ prev }})
+//! ```
+
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use eyre::{Context, ensure};
+use regex::Regex;
+
+/// Template chosen for the notes with a path matching the pattern.
+#[derive(Debug, Clone)]
+pub struct TemplatePattern {
+    pattern: Regex,
+    pub name: String,
+}
+
+impl TemplatePattern {
+    /// Creates the pattern from a glob, where `*` and `?` don't match the `/` while `**` does.
+    pub fn new(glob: &str, name: String) -> Result<Self, regex::Error> {
+        let mut pattern = String::from("^");
+        let mut chars = glob.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.next_if_eq(&'*').is_some() => {
+                    // Also match no directory for `a/**/b`.
+                    if chars.next_if_eq(&'/').is_some() {
+                        pattern.push_str("(?:.*/)?");
+                    } else {
+                        pattern.push_str(".*");
+                    }
+                }
+                '*' => pattern.push_str("[^/]*"),
+                '?' => pattern.push_str("[^/]"),
+                c => pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+
+        pattern.push('$');
+
+        Ok(Self {
+            pattern: Regex::new(&pattern)?,
+            name,
+        })
+    }
+
+    /// Checks the path relative to the notes directory.
+    pub fn matches(&self, path: &Path) -> bool {
+        path.to_str()
+            .is_some_and(|path| self.pattern.is_match(path))
+    }
+}
+
+/// Reads the `<name>.md` template from the first directory containing it.
+///
+/// The name must be a file name, so the template can't be outside of the directories.
+pub fn load(dirs: &[PathBuf], name: &str) -> eyre::Result<String> {
+    let mut components = Path::new(name).components();
+
+    ensure!(
+        matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        ) && !name.contains(['/', '\\']),
+        "invalid template name {name}, expected a file name without the extension"
+    );
+
+    for dir in dirs {
+        let path = dir.join(format!("{name}.md"));
+
+        match fs::read_to_string(&path) {
+            Ok(source) => return Ok(source),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("couldn't read template {}", path.display()));
+            }
+        }
+    }
+
+    let dirs: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+
+    eyre::bail!("couldn't find the template {name} in: {}", dirs.join(", "))
+}
+
+/// Replaces the `{{ variable }}` placeholders with the values of the variables.
+pub fn render(source: &str, vars: &[(&str, &str)]) -> Result<String, TemplateError> {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(start) = rest.find("{{") {
+        let line = || {
+            source[..source.len() - rest.len() + start]
+                .matches('\n')
+                .count()
+                + 1
+        };
+
+        out.push_str(&rest[..start]);
+
+        let Some(end) = rest[start..].find("}}") else {
+            return Err(TemplateError::Unclosed { line: line() });
+        };
+
+        let name = rest[start + 2..start + end].trim();
+
+        let Some((_, value)) = vars.iter().find(|(var, _)| *var == name) else {
+            return Err(TemplateError::Variable {
+                name: name.to_string(),
+                line: line(),
+            });
+        };
+
+        out.push_str(value);
+
+        rest = &rest[start + end + 2..];
+    }
+
+    out.push_str(rest);
+
+    Ok(out)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+    #[error("unclosed placeholder at line {line}")]
+    Unclosed { line: usize },
+    #[error("unknown variable {name} at line {line}")]
+    Variable { name: String, line: usize },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_render_variables() {
+        let vars = [("title", "Foo"), ("prev", "")];
+
+        assert_eq!(
+            render("# {{ title }}\n[prev]({{prev}}) {title}\n", &vars).unwrap(),
+            "# Foo\n[prev]() {title}\n"
+        );
+
+        assert!(matches!(
+            render("a\n{{ date }}", &vars),
+            Err(TemplateError::Variable { line: 2, .. })
+        ));
+        assert!(matches!(
+            render("{{ title", &vars),
+            Err(TemplateError::Unclosed { line: 1 })
+        ));
+    }
+
+    #[test]
+    fn should_load_templates_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let dirs = [dir.path().join("templates")];
+
+        fs::create_dir(&dirs[0]).unwrap();
+        fs::write(dirs[0].join("weekly.md"), "weekly").unwrap();
+        fs::write(dirs[0].join("weekly.v2.md"), "weekly v2").unwrap();
+        fs::write(dir.path().join("secret.md"), "secret").unwrap();
+
+        assert_eq!(load(&dirs, "weekly").unwrap(), "weekly");
+        assert_eq!(load(&dirs, "weekly.v2").unwrap(), "weekly v2");
+
+        for invalid in ["../secret", "..", ".", "", "sub/weekly", "/tmp/secret"] {
+            assert!(load(&dirs, invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn should_match_patterns() {
+        let pattern = TemplatePattern::new("meetings/**/*.md", String::new()).unwrap();
+
+        assert!(pattern.matches(Path::new("meetings/standup.md")));
+        assert!(pattern.matches(Path::new("meetings/2025/01/standup.md")));
+        assert!(!pattern.matches(Path::new("other/meetings/standup.md")));
+
+        let pattern = TemplatePattern::new("*.md", String::new()).unwrap();
+
+        assert!(pattern.matches(Path::new("todo.md")));
+        assert!(!pattern.matches(Path::new("sub/todo.md")));
+    }
+}